@@ -0,0 +1,49 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::context::trim_context;
+use crate::FileDiff;
+use std::borrow::Cow;
+
+/// The number of context lines `git diff` uses by default.
+const CANONICAL_CONTEXT_LINES: usize = 3;
+
+/// Rewrites parsed diffs into a fixed form for reproducible comparison, without eliding anything:
+/// files are sorted by path, `index` lines and carriage returns are dropped from headers, and each
+/// change has at most three lines of context, with hunk headers recomputed to match.
+///
+/// Line endings are not touched here; convert CRLF to LF before parsing for a fully canonical
+/// result.
+pub fn canonicalize(file_diffs: Vec<FileDiff>) -> Vec<FileDiff> {
+    let mut file_diffs = file_diffs
+        .into_iter()
+        .map(|file_diff| {
+            let header = file_diff
+                .header
+                .lines()
+                .filter(|line| !line.starts_with("index "))
+                .map(|line| format!("{line}\n"))
+                .collect::<String>();
+            trim_context(
+                FileDiff {
+                    header: Cow::Owned(header),
+                    chunks: file_diff.chunks,
+                },
+                CANONICAL_CONTEXT_LINES,
+            )
+        })
+        .collect::<Vec<_>>();
+    file_diffs.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+    file_diffs
+}
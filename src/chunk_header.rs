@@ -0,0 +1,164 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ChunkBlock;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+
+/// The parsed form of a `@@ -old_start,old_count +new_start,new_count @@ annotation` line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkHeader {
+    pub old_start: u32,
+    pub old_count: u32,
+    pub new_start: u32,
+    pub new_count: u32,
+    /// The trailing text after the closing `@@`, typically the enclosing function.
+    pub annotation: Option<String>,
+}
+
+impl ChunkHeader {
+    /// Parses a chunk header line, with or without the trailing newline. See `parse_chunk_header`
+    /// to find out what is wrong with a line that does not parse.
+    pub fn parse(s: &str) -> Option<ChunkHeader> {
+        parse_chunk_header(s).ok()
+    }
+
+    /// Returns a copy of this header with the counts recomputed to match what `blocks` render:
+    /// context lines and notes count on both sides, and changed blocks count their removed and
+    /// added lines. Elided blocks are not rendered, so they do not count. The start lines are
+    /// kept, since they refer to positions in the original files.
+    pub fn update_from_blocks(&self, blocks: &[ChunkBlock]) -> ChunkHeader {
+        let (old_count, new_count) =
+            blocks
+                .iter()
+                .fold((0, 0), |(old_count, new_count), block| match block {
+                    ChunkBlock::Context(lines) => {
+                        (old_count + lines.len(), new_count + lines.len())
+                    }
+                    ChunkBlock::Changed(changed) | ChunkBlock::Moved(changed) => (
+                        old_count + changed.removed.len(),
+                        new_count + changed.added.len(),
+                    ),
+                    ChunkBlock::Elided(..) => (old_count, new_count),
+                    ChunkBlock::Note(_) => (old_count + 1, new_count + 1),
+                });
+        ChunkHeader {
+            old_count: old_count as u32,
+            new_count: new_count as u32,
+            ..self.clone()
+        }
+    }
+}
+
+/// Parses a chunk header line, with or without the trailing newline, e.g. from partial diff
+/// output.
+pub fn parse_chunk_header(s: &str) -> Result<ChunkHeader, DiffParseError> {
+    // @@ -27,8 +27,8 @@ AcceleratorCapslockStateMachine::AcceleratorCapslockStateMachine(
+    // The counts are optional and default to 1.
+    static CHUNK_HEADER_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@(?: (.*))?\r?\n?$").unwrap()
+    });
+    let error = |message: &str| DiffParseError {
+        line: s.trim_end_matches(['\r', '\n']).to_owned(),
+        message: message.to_owned(),
+        line_number: None,
+        byte_offset: None,
+    };
+    let captures = CHUNK_HEADER_RE
+        .captures(s)
+        .ok_or_else(|| error("expected a chunk header like `@@ -1,2 +1,3 @@`"))?;
+    let number = |i: usize| match captures.get(i) {
+        Some(m) => m
+            .as_str()
+            .parse()
+            .map_err(|_| error("line number or count is too large")),
+        None => Ok(1),
+    };
+    Ok(ChunkHeader {
+        old_start: number(1)?,
+        old_count: number(2)?,
+        new_start: number(3)?,
+        new_count: number(4)?,
+        annotation: captures
+            .get(5)
+            .map(|m| m.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned),
+    })
+}
+
+impl fmt::Display for ChunkHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_count, self.new_start, self.new_count
+        )?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " {annotation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes malformed diff input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffParseError {
+    /// The offending line, without the trailing newline.
+    pub line: String,
+    pub message: String,
+    /// Where `line` is in the input, counting from 1, if known.
+    pub line_number: Option<usize>,
+    /// Where `line` starts in the input, in bytes from its start, if known.
+    pub byte_offset: Option<usize>,
+}
+
+impl fmt::Display for DiffParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line_number, self.byte_offset) {
+            (Some(line_number), Some(byte_offset)) => {
+                write!(f, "line {line_number} (byte {byte_offset}): ")?
+            }
+            (Some(line_number), None) => write!(f, "line {line_number}: ")?,
+            (None, Some(byte_offset)) => write!(f, "byte {byte_offset}: ")?,
+            (None, None) => (),
+        }
+        write!(f, "{}: {:?}", self.message, self.line)
+    }
+}
+
+impl Error for DiffParseError {}
+
+/// Describes two chunks of the same file found by `FileDiff::sort_chunks` to cover overlapping
+/// lines of the original file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OverlappingChunks {
+    pub file_name: String,
+    /// The first line of the later chunk, which the earlier one also covers.
+    pub line: u32,
+}
+
+impl fmt::Display for OverlappingChunks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: chunks overlap at line {}; the sorted diff may not apply",
+            self.file_name, self.line
+        )
+    }
+}
+
+impl Error for OverlappingChunks {}
@@ -0,0 +1,666 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::heuristics::HeuristicPipeline;
+use crate::lint::{self, LintWarning};
+use crate::replacement::REPLACEMENTS;
+use crate::similarity::SimilarityMetric;
+use crate::{
+    FileFilter, Replacement, ReplacementMatcher, ReplacementRegistry, ReplacementValidationError,
+};
+use regex::{Regex, RegexBuilder};
+use std::error::Error;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const DEFAULT_MAX_LINE_LENGTH: usize = 64 * 1024;
+const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.9;
+/// Caps the memory a user-supplied pattern may take once compiled, since the `regex` crate
+/// bounds matching time by the size of the compiled program rather than with a timeout.
+const FILTER_PATTERN_SIZE_LIMIT: usize = 1 << 20;
+const FILTER_PATTERN_DFA_SIZE_LIMIT: usize = 1 << 20;
+
+/// Knobs controlling how `process_file_diffs` decides what is mechanical.
+pub struct ProcessConfig {
+    pub replacements: Vec<Replacement>,
+    /// The name of the `ReplacementSet` that `replacements` were taken from, if any. Only
+    /// informational once the config is built.
+    pub replacement_set: Option<String>,
+    /// Problems with `replacements` found by `ProcessConfigBuilder::build`. See
+    /// `lint::lint_replacements`.
+    pub lint_warnings: Vec<LintWarning>,
+    /// Search for all `replacements` at once, which is faster when there are many of them. The
+    /// result is the same as searching for each in turn. Takes effect through
+    /// `ProcessConfigBuilder::build`, which prepares `replacement_matcher`.
+    pub parallel_replacements: bool,
+    /// Built from `replacements` if `parallel_replacements` is set. Must be rebuilt if the
+    /// replacements change.
+    pub replacement_matcher: Option<ReplacementMatcher>,
+    /// How malformed input is handled by `parse_file_diffs_with_policy`.
+    pub on_parse_error: ParseErrorPolicy,
+    /// How a pattern added by `ProcessConfigBuilder::attribute_pattern` or `suppression_pattern`
+    /// that `compile_filter_pattern` rejects is handled: it fails the build, or it ends up in
+    /// `rejected_attribute_patterns` or `rejected_suppression_patterns`.
+    pub on_filter_error: FilterErrorPolicy,
+    /// Normalization applied to both sides of a changed block before comparing them.
+    pub pipeline: HeuristicPipeline,
+    /// If set, a deleted file and an added file whose lines are at least this similar, from 0 to
+    /// 1, are shown as a rename instead, with the differences between them as changes to reduce.
+    /// See `detect_renames`.
+    pub rename_detection_threshold: Option<f64>,
+    /// If set, surviving changes are re-emitted with at most this many lines of context and
+    /// recomputed `@@` headers, so the reduced diff still applies to the original files.
+    pub context_lines: Option<usize>,
+    /// Only files whose path matches this filter are processed; the rest are dropped.
+    pub file_filter: Option<FileFilter>,
+    /// The directory the diff is meant to apply to, i.e. a checkout of the original files.
+    pub reference_dir: Option<PathBuf>,
+    /// Before reducing a file, check its context lines against the same file in `reference_dir`.
+    /// A file that does not match, or cannot be read, is passed through verbatim and the first
+    /// mismatch is recorded in `Stats::context_mismatches`, since the diff is evidently not for
+    /// these files and eliding anything could hide a real change. Ignored unless `reference_dir`
+    /// is set.
+    pub require_context_match: bool,
+    /// Blocks that only add lines matching one of these patterns, immediately followed by an
+    /// unchanged line (presumably the declaration being annotated), are elided. Since only the
+    /// diff is available, an attribute added right before a changed line or at the end of a
+    /// chunk is always kept.
+    pub attribute_patterns: Vec<Regex>,
+    /// Blocks that only add lines matching one of these patterns, e.g. `(void)x;` to silence an
+    /// unused variable warning, are elided. A block that also adds anything else, or removes
+    /// anything, is kept as a whole.
+    pub suppression_patterns: Vec<Regex>,
+    /// Attribute patterns that could not be compiled. A block that would have been checked
+    /// against them, i.e. one that `attribute_patterns` alone does not elide, is kept or elided
+    /// as `on_filter_error` says.
+    pub rejected_attribute_patterns: Vec<String>,
+    /// Like `rejected_attribute_patterns`, for suppression patterns.
+    pub rejected_suppression_patterns: Vec<String>,
+    /// Only emit files where at least one changed block was elided. Files the heuristics left
+    /// untouched are dropped, as are files where every change was elided.
+    pub only_reduced_files: bool,
+    /// Keep files where every change was elided, as just their header, to show that they were
+    /// part of the diff. A header without chunks does not apply.
+    pub include_unchanged_files: bool,
+    /// If set, only the first this many surviving chunks of each file are emitted, followed by a
+    /// note saying how many more there were.
+    pub keep_first: Option<NonZeroUsize>,
+    /// Changed blocks with a line longer than this many bytes, e.g. from a minified bundle, are
+    /// kept without being normalized, since comparing them could take a long time.
+    pub max_line_length: usize,
+    /// Elide blocks that only add lines opening or closing a namespace around existing code. See
+    /// `ElisionReason::ScopeWrapper`.
+    pub elide_scope_wrappers: bool,
+    /// Files with fewer than this many removed and added lines left after reduction are dropped
+    /// entirely, as not worth reviewing.
+    pub min_file_changes: Option<usize>,
+    /// Files with more than this many removed and added lines, e.g. from an automated refactor,
+    /// are passed through verbatim instead of being reduced.
+    pub max_file_changes: Option<usize>,
+    /// How the two sides of a changed block are compared after normalization and replacements.
+    /// Anything but `SimilarityMetric::Exact` lets blocks that are merely similar be elided.
+    pub similarity_metric: SimilarityMetric,
+    /// The similarity, from 0 to 1, at which `similarity_metric` considers a block mechanical.
+    pub similarity_threshold: f64,
+    /// Elide changed blocks whose removed and added lines are byte-for-byte identical before
+    /// anything else is checked, even if normalization would not consider them equal, e.g. for
+    /// lines longer than `max_line_length`.
+    pub elide_identical_removed_added: bool,
+    /// Elide pairs of changed blocks in the same file that undo each other. See
+    /// `ElisionReason::NetNoop`.
+    pub elide_net_noops: bool,
+    /// If set, every change left in a chunk whose `Chunk::change_ratio` is below this, from 0 to
+    /// 1, is elided, since a lone change amid a lot of context is likely trivial. See
+    /// `ElisionReason::LowChangeRatio`.
+    pub min_change_ratio: Option<f64>,
+    /// Leave a note where each changed block was elided in a surviving chunk. The notes are
+    /// rendered as context lines, so the result is for review and does not apply. Ignored if
+    /// `context_lines` is set, since elided blocks become context again.
+    pub annotate_elisions: bool,
+    /// Include the `ElisionReason` in each note left by `annotate_elisions`.
+    pub annotate_elision_reasons: bool,
+}
+
+impl ProcessConfig {
+    /// Starts building a config from the defaults.
+    pub fn builder() -> ProcessConfigBuilder {
+        ProcessConfigBuilder {
+            config: ProcessConfig::default(),
+            registry: ReplacementRegistry::default(),
+            extra_replacements: Vec::new(),
+            extra_attribute_patterns: Vec::new(),
+            extra_suppression_patterns: Vec::new(),
+        }
+    }
+
+    /// Looks for settings that are valid but probably not what was intended.
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        if self.replacements.is_empty() {
+            warnings.push(ValidationWarning::NoReplacements);
+        }
+        if self.similarity_metric != SimilarityMetric::Exact && self.similarity_threshold < 0.5 {
+            warnings.push(ValidationWarning::LowSimilarityThreshold {
+                threshold: self.similarity_threshold,
+            });
+        }
+        warnings.extend(
+            self.replacements
+                .iter()
+                .filter(|replacement| {
+                    replacement.pattern.is_none() && replacement.before == replacement.after
+                })
+                .map(|replacement| ValidationWarning::NoOpReplacement {
+                    before: replacement.before.to_string(),
+                }),
+        );
+        warnings
+    }
+
+    /// Compiles a user-supplied pattern, refusing one that would take more than a megabyte once
+    /// compiled, e.g. `\w{1000}{1000}`, since matching it could take a very long time.
+    pub fn compile_filter_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(pattern)
+            .size_limit(FILTER_PATTERN_SIZE_LIMIT)
+            .dfa_size_limit(FILTER_PATTERN_DFA_SIZE_LIMIT)
+            .build()
+    }
+
+    /// Patterns matching a bare deprecation attribute in C++, Java and Rust.
+    pub fn deprecated_attribute_patterns() -> Vec<Regex> {
+        [
+            r"^\s*\[\[deprecated(\(.*\))?\]\]\s*$",
+            r"^\s*@Deprecated(\(.*\))?\s*$",
+            r"^\s*#\[deprecated(\(.*\))?\]\s*$",
+        ]
+        .into_iter()
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect()
+    }
+
+    /// Patterns matching a statement that only silences an unused variable warning: `(void)x;`
+    /// or `std::ignore = x;` in C++, and `let _ = x;` or `_ = x;` in Rust.
+    pub fn unused_variable_suppression_patterns() -> Vec<Regex> {
+        [
+            r"^\s*\(void\)\s*[\w.:>-]+\s*;\s*$",
+            r"^\s*std::ignore\s*=\s*[\w.:>-]+\s*;\s*$",
+            r"^\s*(let\s+)?_\s*=\s*&?[\w.:]+\s*;\s*$",
+        ]
+        .into_iter()
+        .map(|pattern| Regex::new(pattern).unwrap())
+        .collect()
+    }
+}
+
+/// What to do with problems found while parsing the input, e.g. a chunk with fewer lines than its
+/// header says.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseErrorPolicy {
+    /// Stop at the first problem.
+    Fail,
+    /// Log each problem and skip the chunks they were found in.
+    Warn,
+    /// Parse malformed chunks as well as possible.
+    #[default]
+    Ignore,
+}
+
+impl ParseErrorPolicy {
+    pub const ALL: &'static [ParseErrorPolicy] = &[
+        ParseErrorPolicy::Fail,
+        ParseErrorPolicy::Warn,
+        ParseErrorPolicy::Ignore,
+    ];
+}
+
+impl fmt::Display for ParseErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorPolicy::Fail => write!(f, "fail"),
+            ParseErrorPolicy::Warn => write!(f, "warn"),
+            ParseErrorPolicy::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl FromStr for ParseErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ParseErrorPolicy::ALL
+            .iter()
+            .find(|policy| policy.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                let policies = ParseErrorPolicy::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown parse error policy {s:?}; expected one of {}",
+                    policies.join(", ")
+                )
+            })
+    }
+}
+
+/// What to do with a filter pattern that cannot be used, e.g. because it is too large.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterErrorPolicy {
+    /// Fail to build the config.
+    #[default]
+    Fail,
+    /// Log the problem, and keep each block the pattern would have been checked against, with
+    /// another warning.
+    Warn,
+    /// Elide each block the pattern would have been checked against, without saying so.
+    Ignore,
+}
+
+impl FilterErrorPolicy {
+    pub const ALL: &'static [FilterErrorPolicy] = &[
+        FilterErrorPolicy::Fail,
+        FilterErrorPolicy::Warn,
+        FilterErrorPolicy::Ignore,
+    ];
+}
+
+impl fmt::Display for FilterErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterErrorPolicy::Fail => write!(f, "fail"),
+            FilterErrorPolicy::Warn => write!(f, "warn"),
+            FilterErrorPolicy::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl FromStr for FilterErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FilterErrorPolicy::ALL
+            .iter()
+            .find(|policy| policy.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                let policies = FilterErrorPolicy::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown filter error policy {s:?}; expected one of {}",
+                    policies.join(", ")
+                )
+            })
+    }
+}
+
+/// A suspicious but not invalid setting found by `ProcessConfig::validate`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationWarning {
+    /// A replacement whose `before` and `after` are identical never changes anything.
+    NoOpReplacement { before: String },
+    /// With no replacements, only the normalization heuristics can elide anything.
+    NoReplacements,
+    /// A similarity threshold below 0.5 elides blocks that are mostly different.
+    ///
+    /// The threshold is the similarity a block needs to be elided, so it is a low threshold, not a
+    /// high one, that risks eliding real changes; a threshold above 0.5 is on the safe side and is
+    /// not warned about.
+    LowSimilarityThreshold { threshold: f64 },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationWarning::NoOpReplacement { before } => {
+                write!(f, "replacement {before:?} has identical before and after")
+            }
+            ValidationWarning::LowSimilarityThreshold { threshold } => write!(
+                f,
+                "similarity threshold {threshold} elides blocks that are mostly different"
+            ),
+            ValidationWarning::NoReplacements => write!(
+                f,
+                "no replacements are configured; only normalization heuristics will apply"
+            ),
+        }
+    }
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        ProcessConfig {
+            replacements: REPLACEMENTS.to_vec(),
+            replacement_set: Some(ReplacementRegistry::DEFAULT_SET.to_owned()),
+            lint_warnings: Vec::new(),
+            parallel_replacements: false,
+            replacement_matcher: None,
+            on_parse_error: ParseErrorPolicy::default(),
+            on_filter_error: FilterErrorPolicy::default(),
+            pipeline: HeuristicPipeline::default(),
+            rename_detection_threshold: None,
+            context_lines: None,
+            file_filter: None,
+            reference_dir: None,
+            require_context_match: false,
+            attribute_patterns: Vec::new(),
+            suppression_patterns: Vec::new(),
+            rejected_attribute_patterns: Vec::new(),
+            rejected_suppression_patterns: Vec::new(),
+            only_reduced_files: false,
+            include_unchanged_files: false,
+            keep_first: None,
+            max_line_length: DEFAULT_MAX_LINE_LENGTH,
+            elide_scope_wrappers: false,
+            min_file_changes: None,
+            max_file_changes: None,
+            similarity_metric: SimilarityMetric::Exact,
+            similarity_threshold: DEFAULT_SIMILARITY_THRESHOLD,
+            elide_identical_removed_added: true,
+            elide_net_noops: false,
+            min_change_ratio: None,
+            annotate_elisions: false,
+            annotate_elision_reasons: false,
+        }
+    }
+}
+
+/// Builds a `ProcessConfig`, validating it once everything is set.
+pub struct ProcessConfigBuilder {
+    config: ProcessConfig,
+    registry: ReplacementRegistry,
+    /// Added by `replacement`, on top of the chosen set.
+    extra_replacements: Vec<Replacement>,
+    /// Added by `attribute_pattern` and `suppression_pattern`, compiled by `build`.
+    extra_attribute_patterns: Vec<String>,
+    extra_suppression_patterns: Vec<String>,
+}
+
+impl ProcessConfigBuilder {
+    /// Replaces the default replacements, without naming them as a set.
+    pub fn replacements(mut self, replacements: Vec<Replacement>) -> Self {
+        self.config.replacements = replacements;
+        self.config.replacement_set = None;
+        self
+    }
+
+    /// Adds a replacement to the chosen ones.
+    pub fn replacement(mut self, replacement: Replacement) -> Self {
+        self.extra_replacements.push(replacement);
+        self
+    }
+
+    /// Looks up the sets named by `replacement_set` in `registry` instead of the built-in one.
+    pub fn registry(mut self, registry: ReplacementRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Uses the replacements of the set registered as `name`, checked by `build`.
+    pub fn replacement_set(mut self, name: impl Into<String>) -> Self {
+        self.config.replacement_set = Some(name.into());
+        self
+    }
+
+    pub fn pipeline(mut self, pipeline: HeuristicPipeline) -> Self {
+        self.config.pipeline = pipeline;
+        self
+    }
+
+    pub fn context_lines(mut self, context_lines: Option<usize>) -> Self {
+        self.config.context_lines = context_lines;
+        self
+    }
+
+    pub fn file_filter(mut self, file_filter: Option<FileFilter>) -> Self {
+        self.config.file_filter = file_filter;
+        self
+    }
+
+    pub fn reference_dir(mut self, reference_dir: Option<PathBuf>) -> Self {
+        self.config.reference_dir = reference_dir;
+        self
+    }
+
+    pub fn require_context_match(mut self, require_context_match: bool) -> Self {
+        self.config.require_context_match = require_context_match;
+        self
+    }
+
+    pub fn attribute_patterns(mut self, attribute_patterns: Vec<Regex>) -> Self {
+        self.config.attribute_patterns = attribute_patterns;
+        self
+    }
+
+    pub fn suppression_patterns(mut self, suppression_patterns: Vec<Regex>) -> Self {
+        self.config.suppression_patterns = suppression_patterns;
+        self
+    }
+
+    /// Adds a user-supplied pattern to `attribute_patterns`, compiled by `build` subject to
+    /// `on_filter_error`.
+    pub fn attribute_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.extra_attribute_patterns.push(pattern.into());
+        self
+    }
+
+    /// Adds a user-supplied pattern to `suppression_patterns`, compiled by `build` subject to
+    /// `on_filter_error`.
+    pub fn suppression_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.extra_suppression_patterns.push(pattern.into());
+        self
+    }
+
+    pub fn parallel_replacements(mut self, parallel_replacements: bool) -> Self {
+        self.config.parallel_replacements = parallel_replacements;
+        self
+    }
+
+    pub fn rename_detection_threshold(mut self, rename_detection_threshold: Option<f64>) -> Self {
+        self.config.rename_detection_threshold = rename_detection_threshold;
+        self
+    }
+
+    pub fn on_filter_error(mut self, on_filter_error: FilterErrorPolicy) -> Self {
+        self.config.on_filter_error = on_filter_error;
+        self
+    }
+
+    pub fn only_reduced_files(mut self, only_reduced_files: bool) -> Self {
+        self.config.only_reduced_files = only_reduced_files;
+        self
+    }
+
+    pub fn include_unchanged_files(mut self, include_unchanged_files: bool) -> Self {
+        self.config.include_unchanged_files = include_unchanged_files;
+        self
+    }
+
+    pub fn keep_first(mut self, keep_first: Option<NonZeroUsize>) -> Self {
+        self.config.keep_first = keep_first;
+        self
+    }
+
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.config.max_line_length = max_line_length;
+        self
+    }
+
+    pub fn elide_scope_wrappers(mut self, elide_scope_wrappers: bool) -> Self {
+        self.config.elide_scope_wrappers = elide_scope_wrappers;
+        self
+    }
+
+    pub fn min_file_changes(mut self, min_file_changes: Option<usize>) -> Self {
+        self.config.min_file_changes = min_file_changes;
+        self
+    }
+
+    pub fn max_file_changes(mut self, max_file_changes: Option<usize>) -> Self {
+        self.config.max_file_changes = max_file_changes;
+        self
+    }
+
+    pub fn similarity_metric(mut self, similarity_metric: SimilarityMetric) -> Self {
+        self.config.similarity_metric = similarity_metric;
+        self
+    }
+
+    pub fn similarity_threshold(mut self, similarity_threshold: f64) -> Self {
+        self.config.similarity_threshold = similarity_threshold;
+        self
+    }
+
+    pub fn on_parse_error(mut self, on_parse_error: ParseErrorPolicy) -> Self {
+        self.config.on_parse_error = on_parse_error;
+        self
+    }
+
+    pub fn elide_identical_removed_added(mut self, elide_identical_removed_added: bool) -> Self {
+        self.config.elide_identical_removed_added = elide_identical_removed_added;
+        self
+    }
+
+    pub fn elide_net_noops(mut self, elide_net_noops: bool) -> Self {
+        self.config.elide_net_noops = elide_net_noops;
+        self
+    }
+
+    pub fn min_change_ratio(mut self, min_change_ratio: Option<f64>) -> Self {
+        self.config.min_change_ratio = min_change_ratio;
+        self
+    }
+
+    pub fn annotate_elisions(mut self, annotate_elisions: bool) -> Self {
+        self.config.annotate_elisions = annotate_elisions;
+        self
+    }
+
+    pub fn annotate_elision_reasons(mut self, annotate_elision_reasons: bool) -> Self {
+        self.config.annotate_elision_reasons = annotate_elision_reasons;
+        self
+    }
+
+    /// Resolves the replacement set and validates every replacement, returning the first
+    /// problem found. Replacements that are valid but overlap are recorded in `lint_warnings`.
+    pub fn build(mut self) -> Result<ProcessConfig, ConfigError> {
+        if let Some(name) = &self.config.replacement_set {
+            let set =
+                self.registry
+                    .get(name)
+                    .ok_or_else(|| ConfigError::UnknownReplacementSet {
+                        name: name.clone(),
+                        known: self.registry.names().into_iter().map(Into::into).collect(),
+                    })?;
+            self.config.replacements = set.replacements.clone();
+        }
+        self.config.replacements.extend(self.extra_replacements);
+        for replacement in &self.config.replacements {
+            replacement.validate()?;
+        }
+        self.config.lint_warnings = lint::lint_replacements(&self.config.replacements);
+        if self.config.parallel_replacements {
+            // Too many replacements for one automaton only costs speed.
+            self.config.replacement_matcher =
+                ReplacementMatcher::new(&self.config.replacements).ok();
+        }
+
+        let policy = self.config.on_filter_error;
+        for (patterns, rejected_patterns, extra_patterns) in [
+            (
+                &mut self.config.attribute_patterns,
+                &mut self.config.rejected_attribute_patterns,
+                self.extra_attribute_patterns,
+            ),
+            (
+                &mut self.config.suppression_patterns,
+                &mut self.config.rejected_suppression_patterns,
+                self.extra_suppression_patterns,
+            ),
+        ] {
+            for pattern in extra_patterns {
+                match ProcessConfig::compile_filter_pattern(&pattern) {
+                    Ok(regex) => patterns.push(regex),
+                    Err(error) => {
+                        match policy {
+                            FilterErrorPolicy::Fail => {
+                                return Err(ConfigError::InvalidPattern {
+                                    pattern,
+                                    message: error.to_string(),
+                                })
+                            }
+                            FilterErrorPolicy::Warn => {
+                                log::warn!("cannot use pattern {pattern:?}: {error}");
+                            }
+                            FilterErrorPolicy::Ignore => (),
+                        }
+                        rejected_patterns.push(pattern);
+                    }
+                }
+            }
+        }
+        Ok(self.config)
+    }
+}
+
+/// Describes why `ProcessConfigBuilder::build` failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    InvalidReplacement(ReplacementValidationError),
+    /// A pattern added by `attribute_pattern` or `suppression_pattern` does not compile.
+    InvalidPattern {
+        pattern: String,
+        message: String,
+    },
+    /// No set is registered under the chosen name.
+    UnknownReplacementSet {
+        name: String,
+        known: Vec<String>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidReplacement(error) => write!(f, "{error}"),
+            ConfigError::InvalidPattern { pattern, message } => {
+                write!(f, "invalid pattern {pattern:?}: {message}")
+            }
+            ConfigError::UnknownReplacementSet { name, known } => write!(
+                f,
+                "unknown replacement set {name:?}; known sets: {}",
+                known.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::InvalidReplacement(error) => Some(error),
+            ConfigError::InvalidPattern { .. } | ConfigError::UnknownReplacementSet { .. } => None,
+        }
+    }
+}
+
+impl From<ReplacementValidationError> for ConfigError {
+    fn from(error: ReplacementValidationError) -> Self {
+        ConfigError::InvalidReplacement(error)
+    }
+}
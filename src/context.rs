@@ -0,0 +1,136 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Changed, Chunk, ChunkBlock, ChunkHeader, FileDiff};
+use std::borrow::Cow;
+
+enum Line<'a> {
+    Context(&'a str),
+    Changed(Changed<'a>),
+}
+
+impl<'a> Line<'a> {
+    fn old_len(&self) -> usize {
+        match self {
+            Line::Context(_) => 1,
+            Line::Changed(changed) => changed.removed.len(),
+        }
+    }
+
+    fn new_len(&self) -> usize {
+        match self {
+            Line::Context(_) => 1,
+            Line::Changed(changed) => changed.added.len(),
+        }
+    }
+}
+
+/// Re-splits the chunks of `file_diff` so that each surviving change has at most `context_lines`
+/// lines of context around it, recomputing the `@@` headers to match.
+///
+/// Elided blocks are turned back into context using their removed lines, since the reduced diff
+/// no longer applies them. New-side line numbers only account for the surviving changes. Chunks
+/// with a header that cannot be parsed are passed through untouched.
+pub(crate) fn trim_context(file_diff: FileDiff, context_lines: usize) -> FileDiff {
+    let FileDiff { header, chunks } = file_diff;
+    // The net number of lines added by the surviving changes seen so far in this file.
+    let mut delta: i64 = 0;
+    let mut new_chunks = Vec::new();
+    for chunk in chunks {
+        let Some(chunk_header) = ChunkHeader::parse(&chunk.header) else {
+            new_chunks.push(chunk);
+            continue;
+        };
+        let mut lines = Vec::new();
+        for block in chunk.blocks {
+            match block {
+                ChunkBlock::Context(context) => {
+                    lines.extend(context.into_iter().map(Line::Context))
+                }
+                ChunkBlock::Changed(changed) | ChunkBlock::Moved(changed) => {
+                    lines.push(Line::Changed(changed))
+                }
+                ChunkBlock::Elided(changed, _) => {
+                    lines.extend(changed.removed.into_iter().map(Line::Context))
+                }
+                // Not part of the original file, so the recomputed headers can't include it.
+                ChunkBlock::Note(_) => (),
+            }
+        }
+
+        // An empty range is described by the line before it, e.g. `@@ -0,0 +1,3 @@`.
+        let mut old_line =
+            chunk_header.old_start as usize + usize::from(chunk_header.old_count == 0);
+        let mut old_lines_at = Vec::with_capacity(lines.len());
+        for line in &lines {
+            old_lines_at.push(old_line);
+            old_line += line.old_len();
+        }
+
+        let changed_indices = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line, Line::Changed(_)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        // Group changes that are close enough for their context to touch or overlap.
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        for &i in &changed_indices {
+            match groups.last_mut() {
+                Some((_, last)) if i - *last - 1 <= 2 * context_lines => *last = i,
+                _ => groups.push((i, i)),
+            }
+        }
+
+        let mut lines = lines.into_iter().map(Some).collect::<Vec<_>>();
+        for (first, last) in groups {
+            let start = first.saturating_sub(context_lines);
+            let end = (last + 1 + context_lines).min(lines.len());
+            let old_start = old_lines_at[start];
+            let new_start = (old_start as i64 + delta) as usize;
+            let mut old_count = 0;
+            let mut new_count = 0;
+            let mut blocks = Vec::new();
+            for line in lines[start..end].iter_mut().filter_map(Option::take) {
+                old_count += line.old_len();
+                new_count += line.new_len();
+                match line {
+                    Line::Context(context) => match blocks.last_mut() {
+                        Some(ChunkBlock::Context(lines)) => lines.push(context),
+                        _ => blocks.push(ChunkBlock::Context(vec![context])),
+                    },
+                    Line::Changed(changed) => {
+                        delta += changed.added.len() as i64 - changed.removed.len() as i64;
+                        blocks.push(ChunkBlock::Changed(changed));
+                    }
+                }
+            }
+            let header = ChunkHeader {
+                old_start: (old_start - usize::from(old_count == 0)) as u32,
+                old_count: old_count as u32,
+                new_start: (new_start - usize::from(new_count == 0)) as u32,
+                new_count: new_count as u32,
+                annotation: chunk_header.annotation.clone(),
+            };
+            new_chunks.push(Chunk {
+                header: Cow::Owned(format!("{header}\n")),
+                blocks,
+            });
+        }
+    }
+    FileDiff {
+        header,
+        chunks: new_chunks,
+    }
+}
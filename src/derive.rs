@@ -0,0 +1,100 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ChunkBlock, FileDiff, Replacement};
+use std::collections::HashMap;
+
+/// The share of substitutions of a given `before` that must agree on the same `after`.
+const MIN_AGREEMENT: f64 = 0.9;
+
+/// Derives replacements from a diff known to be mechanical, e.g. the commit that performed a
+/// migration, so the same migration can be elided from another diff.
+///
+/// Only high-confidence substitutions are returned. Changed blocks are compared line by line, so
+/// only blocks with as many removed as added lines are used. In each pair of lines, the part that
+/// differs, widened to whole identifiers, is one substitution; e.g. `NOTREACHED_NORETURN();` and
+/// `NOTREACHED();` give `NOTREACHED_NORETURN -> NOTREACHED`. A substitution becomes a
+/// replacement if it was seen at least `min_occurrences` times, and at least 90% of the
+/// substitutions of its `before` side agree on its `after` side. Lines that differ in more than one
+/// place produce a long substitution that rarely repeats, so they are effectively ignored.
+pub fn derive_replacements(file_diffs: &[FileDiff], min_occurrences: usize) -> Vec<Replacement> {
+    let mut counts: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    let blocks = file_diffs
+        .iter()
+        .flat_map(|file_diff| &file_diff.chunks)
+        .flat_map(|chunk| &chunk.blocks);
+    for block in blocks {
+        let ChunkBlock::Changed(changed) = block else {
+            continue;
+        };
+        if changed.removed.len() != changed.added.len() {
+            continue;
+        }
+        for (removed, added) in changed.removed.iter().zip(&changed.added) {
+            if let Some((before, after)) = substitution(removed, added) {
+                *counts.entry(before).or_default().entry(after).or_default() += 1;
+            }
+        }
+    }
+
+    let mut replacements = counts
+        .into_iter()
+        .filter_map(|(before, afters)| {
+            let total = afters.values().sum::<usize>();
+            let (after, count) = afters
+                .into_iter()
+                .max_by_key(|&(after, count)| (count, std::cmp::Reverse(after)))?;
+            (count >= min_occurrences && count as f64 >= MIN_AGREEMENT * total as f64)
+                .then(|| Replacement::new(before.to_owned(), after.to_owned()))
+        })
+        .collect::<Vec<_>>();
+    replacements.sort_by(|a, b| a.before.cmp(&b.before));
+    replacements
+}
+
+/// Returns the differing parts of two lines, widened to whole identifiers, if both are non-blank.
+fn substitution<'a>(removed: &'a str, added: &'a str) -> Option<(&'a str, &'a str)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut prefix = removed
+        .char_indices()
+        .zip(added.chars())
+        .find(|&((_, a), b)| a != b)
+        .map_or(removed.len().min(added.len()), |((i, _), _)| i);
+    let mut suffix = removed
+        .chars()
+        .rev()
+        .zip(added.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+    // The prefix and suffix may overlap, e.g. for `aa` and `aaa`.
+    suffix = suffix.min(removed.len() - prefix).min(added.len() - prefix);
+
+    // Widen to whole identifiers, so that e.g. `Foo` -> `Bar` is found as `FooBaz` -> `BarBaz`
+    // rather than `Foo` -> `Bar` only when that makes up the whole identifier.
+    while prefix > 0 && removed[..prefix].ends_with(is_word) {
+        prefix -= removed[..prefix].chars().next_back().unwrap().len_utf8();
+    }
+    while suffix > 0 && removed[removed.len() - suffix..].starts_with(is_word) {
+        suffix -= removed[removed.len() - suffix..]
+            .chars()
+            .next()
+            .unwrap()
+            .len_utf8();
+    }
+
+    let before = removed[prefix..removed.len() - suffix].trim();
+    let after = added[prefix..added.len() - suffix].trim();
+    (!before.is_empty() && !after.is_empty() && before != after).then_some((before, after))
+}
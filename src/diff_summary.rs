@@ -0,0 +1,113 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::json::write_string;
+use crate::{FileDiff, Stats};
+use std::fmt::Write;
+
+/// What is left of one file after reduction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileSummary {
+    pub file_path: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    /// `added_lines - removed_lines`.
+    pub net_delta: i64,
+    pub chunks_retained: usize,
+    /// How many changed blocks were elided from the file.
+    pub elided_blocks: usize,
+    /// The replacements found in the file before reduction, by their `before` side, in sorted
+    /// order. See `FileStats::matched_rules`.
+    pub matched_rules: Vec<String>,
+}
+
+impl FileSummary {
+    /// Summarizes a reduced `diff`, from which `elided` changed blocks were elided. The original
+    /// diff is gone, so `matched_rules` is left empty.
+    pub fn from_file_diff(diff: &FileDiff, elided: usize) -> FileSummary {
+        let (removed_lines, added_lines) = diff.total_changed_lines();
+        FileSummary {
+            file_path: diff.file_name().to_owned(),
+            added_lines,
+            removed_lines,
+            net_delta: added_lines as i64 - removed_lines as i64,
+            chunks_retained: diff.chunks.len(),
+            elided_blocks: elided,
+            matched_rules: Vec::new(),
+        }
+    }
+}
+
+/// A machine-readable report of a reduction, with a summary of each file in the output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub files: Vec<FileSummary>,
+}
+
+impl Report {
+    /// Summarizes `file_diffs`, as returned by `process_file_diffs_with_stats` along with `stats`.
+    /// The files must be in the order they were returned, to match them up with `stats.per_file`.
+    pub fn new(file_diffs: &[FileDiff], stats: &Stats) -> Report {
+        let mut kept = stats.per_file.iter().filter(|file| file.kept);
+        let files = file_diffs
+            .iter()
+            .map(|file_diff| {
+                let file_stats = kept.next();
+                FileSummary {
+                    matched_rules: file_stats
+                        .map(|file| file.matched_rules.clone())
+                        .unwrap_or_default(),
+                    ..FileSummary::from_file_diff(
+                        file_diff,
+                        file_stats.map_or(0, |file| file.elided_blocks),
+                    )
+                }
+            })
+            .collect();
+        Report { files }
+    }
+
+    /// Renders the report as a JSON document, `{"files": [...]}`, with one object per file holding
+    /// the fields of `FileSummary`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"files\":[");
+        for (i, file) in self.files.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"file_path\":");
+            write_string(&mut out, &file.file_path);
+            write!(
+                out,
+                ",\"added_lines\":{},\"removed_lines\":{},\"net_delta\":{},\
+                 \"chunks_retained\":{},\"elided_blocks\":{},\"matched_rules\":[",
+                file.added_lines,
+                file.removed_lines,
+                file.net_delta,
+                file.chunks_retained,
+                file.elided_blocks
+            )
+            .unwrap();
+            for (i, rule) in file.matched_rules.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(&mut out, rule);
+            }
+            out.push_str("]}");
+        }
+        out.push_str("]}\n");
+        out
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::RegexSet;
+use std::error::Error;
+use std::fmt;
+
+/// A set of glob patterns matched against slash-separated paths. `*` and `?` match within one path
+/// component, `**` matches any number of components, and everything else matches literally.
+#[derive(Clone, Debug)]
+pub struct GlobSet {
+    patterns: Vec<String>,
+    set: RegexSet,
+}
+
+impl GlobSet {
+    pub fn new<T: AsRef<str>>(patterns: &[T]) -> Self {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| pattern.as_ref().to_owned())
+            .collect::<Vec<_>>();
+        let set = RegexSet::new(patterns.iter().map(|pattern| glob_to_regex(pattern))).unwrap();
+        GlobSet { patterns, set }
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.set.is_match(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+}
+
+impl Default for GlobSet {
+    fn default() -> Self {
+        GlobSet::new::<&str>(&[])
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut rest = glob;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            regex += "(?:.*/)?";
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("**") {
+            regex += ".*";
+            rest = after;
+        } else {
+            match c {
+                '*' => regex += "[^/]*",
+                '?' => regex += "[^/]",
+                _ => regex += &regex::escape(&c.to_string()),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    regex + "$"
+}
+
+/// Chooses the files to process by their path, as given by `FileDiff::file_name`.
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    /// If not empty, only paths matching one of these are processed.
+    pub include: GlobSet,
+    /// Paths matching one of these are never processed, even if they are included.
+    pub exclude: GlobSet,
+}
+
+impl FileFilter {
+    pub fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.is_match(path)) && !self.exclude.is_match(path)
+    }
+
+    /// Reads the `[file_filter]` table of a TOML config, e.g.
+    ///
+    /// ```toml
+    /// [file_filter]
+    /// include = ["**/*.cc"]
+    /// exclude = ["third_party/**"]
+    /// ```
+    ///
+    /// Both keys are optional. Returns `None` if there is no such table.
+    pub fn from_toml(config: &str) -> Result<Option<FileFilter>, FileFilterError> {
+        let config = config
+            .parse::<toml::Table>()
+            .map_err(|error| FileFilterError(error.to_string()))?;
+        let Some(table) = config.get("file_filter") else {
+            return Ok(None);
+        };
+        let table = table
+            .as_table()
+            .ok_or_else(|| FileFilterError("`file_filter` is not a table".to_owned()))?;
+        if let Some(key) = table
+            .keys()
+            .find(|key| !["include", "exclude"].contains(&key.as_str()))
+        {
+            return Err(FileFilterError(format!("unknown key `file_filter.{key}`")));
+        }
+        let globs = |key: &str| -> Result<GlobSet, FileFilterError> {
+            let error = || FileFilterError(format!("`file_filter.{key}` is not a list of strings"));
+            let patterns = match table.get(key) {
+                Some(value) => value
+                    .as_array()
+                    .ok_or_else(error)?
+                    .iter()
+                    .map(|pattern| pattern.as_str().ok_or_else(error))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => Vec::new(),
+            };
+            Ok(GlobSet::new(&patterns))
+        };
+        Ok(Some(FileFilter {
+            include: globs("include")?,
+            exclude: globs("exclude")?,
+        }))
+    }
+}
+
+/// Describes why `FileFilter::from_toml` failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileFilterError(pub String);
+
+impl fmt::Display for FileFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid file filter: {}", self.0)
+    }
+}
+
+impl Error for FileFilterError {}
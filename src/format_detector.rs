@@ -0,0 +1,89 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+
+/// How much of the input `detect_diff_format` looks at.
+const DETECTION_WINDOW: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// `git diff`, with `diff --git` lines.
+    Git,
+    /// `svn diff`, with `Index:` lines.
+    Svn,
+    /// `hg diff`, with `diff -r` lines.
+    Mercurial,
+    /// A unified diff without any tool-specific lines, e.g. from `diff -u`.
+    Plain,
+    /// An ed script, e.g. from `diff -e`, which cannot be parsed.
+    Ed,
+    /// None of the above, or several of them.
+    Unknown,
+}
+
+impl fmt::Display for DiffFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffFormat::Git => write!(f, "git"),
+            DiffFormat::Svn => write!(f, "svn"),
+            DiffFormat::Mercurial => write!(f, "hg"),
+            DiffFormat::Plain => write!(f, "plain"),
+            DiffFormat::Ed => write!(f, "ed"),
+            DiffFormat::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Guesses the format of `input` from its first kilobyte. Input with markers of more than one
+/// tool, e.g. an svn diff pasted after a git diff, is `DiffFormat::Unknown`.
+pub fn detect_diff_format(input: &str) -> DiffFormat {
+    // An ed command, e.g. `1a`, `2,3c` or `4d`.
+    static ED_COMMAND_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+(?:,\d+)?[acd]$").unwrap());
+
+    let mut end = input.len().min(DETECTION_WINDOW);
+    while !input.is_char_boundary(end) {
+        end -= 1;
+    }
+    let lines = input[..end].lines().collect::<Vec<_>>();
+    let mut formats = Vec::new();
+    for (prefix, format) in [
+        ("diff --git ", DiffFormat::Git),
+        ("Index: ", DiffFormat::Svn),
+        ("diff -r ", DiffFormat::Mercurial),
+    ] {
+        if lines.iter().any(|line| line.starts_with(prefix)) {
+            formats.push(format);
+        }
+    }
+    match formats[..] {
+        [format] => format,
+        [_, _, ..] => DiffFormat::Unknown,
+        [] if lines
+            .windows(2)
+            .any(|pair| pair[0].starts_with("--- ") && pair[1].starts_with("+++ ")) =>
+        {
+            DiffFormat::Plain
+        }
+        [] if lines
+            .first()
+            .is_some_and(|line| ED_COMMAND_RE.is_match(line)) =>
+        {
+            DiffFormat::Ed
+        }
+        [] => DiffFormat::Unknown,
+    }
+}
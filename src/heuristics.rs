@@ -0,0 +1,186 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+
+/// A single step in a `HeuristicPipeline`, rewriting text so that mechanical differences
+/// disappear.
+pub trait Normalizer: Send + Sync {
+    fn normalize(&self, text: &str) -> String;
+    fn name(&self) -> &str;
+}
+
+/// Whitespace is not significant, so join the lines and squash consecutive runs of whitespace
+/// characters into a space.
+pub struct WhitespaceNormalizer;
+
+impl Normalizer for WhitespaceNormalizer {
+    fn normalize(&self, text: &str) -> String {
+        static MULTIPLE_WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s{2,}").unwrap());
+        MULTIPLE_WHITESPACE_RE
+            .replace_all(&text.replace('\n', " "), " ")
+            .into_owned()
+    }
+
+    fn name(&self) -> &str {
+        "whitespace"
+    }
+}
+
+/// Strips leading indentation and then the first matching comment delimiter from each line, to
+/// improve fuzzy matching when comments are reflowed across lines.
+pub struct CommentStripper(pub Vec<String>);
+
+impl Normalizer for CommentStripper {
+    fn normalize(&self, text: &str) -> String {
+        text.split('\n')
+            .map(|line| {
+                let line = line.trim_start();
+                self.0
+                    .iter()
+                    .find_map(|prefix| line.strip_prefix(prefix.as_str()))
+                    .unwrap_or(line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn name(&self) -> &str {
+        "comment-stripper"
+    }
+}
+
+/// Compares the given tokens case-insensitively, e.g. so that `TRUE`, `True` and `true` are the
+/// same after a cleanup, by lowercasing every occurrence of them as a whole word. Everything else
+/// stays case-sensitive.
+pub struct CaseFolder {
+    /// `None` if no tokens were given.
+    tokens_re: Option<Regex>,
+}
+
+impl CaseFolder {
+    pub fn new<T: AsRef<str>>(tokens: &[T]) -> Self {
+        let alternatives = tokens
+            .iter()
+            .map(|token| regex::escape(token.as_ref()))
+            .collect::<Vec<_>>();
+        let tokens_re = (!alternatives.is_empty())
+            .then(|| Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|"))).unwrap());
+        CaseFolder { tokens_re }
+    }
+}
+
+impl Normalizer for CaseFolder {
+    fn normalize(&self, text: &str) -> String {
+        match &self.tokens_re {
+            Some(tokens_re) => tokens_re
+                .replace_all(text, |captures: &regex::Captures| {
+                    captures[0].to_lowercase()
+                })
+                .into_owned(),
+            None => text.to_owned(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "case-folder"
+    }
+}
+
+/// Squashing whitespace tends to produce `( `, e.g. when a function call is reflowed to the
+/// following line, so convert `( ` back to `(`.
+pub struct ParenFixer;
+
+impl Normalizer for ParenFixer {
+    fn normalize(&self, text: &str) -> String {
+        text.replace("( ", "(")
+    }
+
+    fn name(&self) -> &str {
+        "paren-fixer"
+    }
+}
+
+/// An ordered list of normalizers applied to both sides of a changed block before comparing them.
+pub struct HeuristicPipeline {
+    normalizers: Vec<Box<dyn Normalizer>>,
+}
+
+impl HeuristicPipeline {
+    /// Creates a pipeline with no steps, i.e. one that only compares text exactly.
+    pub fn empty() -> Self {
+        HeuristicPipeline {
+            normalizers: Vec::new(),
+        }
+    }
+
+    /// Appends a step to the end of the pipeline.
+    pub fn push(&mut self, normalizer: Box<dyn Normalizer>) {
+        self.normalizers.push(normalizer);
+    }
+
+    pub fn normalizers(&self) -> &[Box<dyn Normalizer>] {
+        &self.normalizers
+    }
+
+    /// Joins `lines` with newlines and runs the result through each step in order.
+    pub fn apply(&self, lines: &[&str]) -> String {
+        self.normalizers
+            .iter()
+            .fold(lines.join("\n"), |text, normalizer| {
+                normalizer.normalize(&text)
+            })
+    }
+
+    /// Runs `sample` through the pipeline, returning one line per step with the intermediate
+    /// output after that step.
+    pub fn debug_trace(&self, sample: &str) -> String {
+        let mut trace = format!("input: {sample:?}\n");
+        let mut text = sample.to_owned();
+        for normalizer in &self.normalizers {
+            text = normalizer.normalize(&text);
+            trace += &format!("{}: {text:?}\n", normalizer.name());
+        }
+        trace
+    }
+}
+
+impl fmt::Debug for HeuristicPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeuristicPipeline")
+            .field(
+                "steps",
+                &self
+                    .normalizers
+                    .iter()
+                    .map(|normalizer| normalizer.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for HeuristicPipeline {
+    fn default() -> Self {
+        HeuristicPipeline {
+            normalizers: vec![
+                Box::new(CommentStripper(vec!["// ".to_owned()])),
+                Box::new(WhitespaceNormalizer),
+                Box::new(ParenFixer),
+            ],
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{process_changed_block, ChunkBlock, ChunkHeader, FileDiff, ProcessConfig, Stats};
+use std::fmt;
+
+/// A single changed block with up to a few lines of context on each side, e.g. to review or apply
+/// it independently of the other changes in its chunk.
+#[derive(Clone, Debug)]
+pub struct Hunk<'a> {
+    /// The path of the changed file, as returned by `FileDiff::file_name`.
+    pub file_path: String,
+    /// The counts match `blocks`, and the start lines are positions in the original diff.
+    pub header: ChunkHeader,
+    /// Context, then the changed block, then context. Either context block is left out if empty.
+    pub blocks: Vec<ChunkBlock<'a>>,
+}
+
+impl Hunk<'_> {
+    /// Renders the hunk as a patch of `file_path` on its own, with synthesized `--- a/` and
+    /// `+++ b/` headers. Unless the hunk contains elided blocks or notes, the patch applies to the
+    /// original file.
+    pub fn to_patch_string(&self) -> String {
+        format!("--- a/{0}\n+++ b/{0}\n{self}", self.file_path)
+    }
+}
+
+/// Renders the `@@` header and the blocks, like a `Chunk`.
+impl fmt::Display for Hunk<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.header)?;
+        for block in &self.blocks {
+            write!(f, "{block}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Decides whether the changed block of `hunk` is mechanical, the same way `process_file_diffs`
+/// does for each block of a chunk, returning `None` if it would be elided. The context after it
+/// is enough to judge added attributes, but the checks that look at several blocks of a file,
+/// `elide_scope_wrappers` and `elide_net_noops`, do not apply to a single hunk.
+pub fn process_hunk<'a>(mut hunk: Hunk<'a>, config: &ProcessConfig) -> Option<Hunk<'a>> {
+    let Some(i) = hunk
+        .blocks
+        .iter()
+        .position(|block| matches!(block, ChunkBlock::Changed(_)))
+    else {
+        return Some(hunk);
+    };
+    let ChunkBlock::Changed(changed) = hunk.blocks.remove(i) else {
+        unreachable!();
+    };
+    let block = process_changed_block(changed, hunk.blocks.get(i), config, &mut Stats::default());
+    if let ChunkBlock::Elided(..) = block {
+        return None;
+    }
+    hunk.blocks.insert(i, block);
+    Some(hunk)
+}
+
+/// Splits every chunk of `diff` into one hunk per changed block, moved or not, each with at most
+/// `context` lines of context taken from the unchanged lines right before and after it. Lines of
+/// other changed blocks never become context, so a hunk can have less than `context` lines of
+/// context on a side. Elided blocks do not become hunks, and chunks whose header cannot be parsed
+/// are skipped.
+pub fn diff_to_hunks<'a>(diff: &FileDiff<'a>, context: usize) -> Vec<Hunk<'a>> {
+    let mut hunks = Vec::new();
+    for chunk in &diff.chunks {
+        let Some(chunk_header) = ChunkHeader::parse(&chunk.header) else {
+            continue;
+        };
+        // An empty range is described by the line before it, e.g. `@@ -0,0 +1,3 @@`.
+        let mut old_line = chunk_header.old_start + u32::from(chunk_header.old_count == 0);
+        let mut new_line = chunk_header.new_start + u32::from(chunk_header.new_count == 0);
+        for (i, block) in chunk.blocks.iter().enumerate() {
+            let changed = match block {
+                ChunkBlock::Context(lines) => {
+                    old_line += lines.len() as u32;
+                    new_line += lines.len() as u32;
+                    continue;
+                }
+                ChunkBlock::Elided(changed, _) => {
+                    old_line += changed.removed.len() as u32;
+                    new_line += changed.added.len() as u32;
+                    continue;
+                }
+                ChunkBlock::Note(_) => continue,
+                ChunkBlock::Changed(changed) | ChunkBlock::Moved(changed) => changed,
+            };
+            let before = match i.checked_sub(1).map(|i| &chunk.blocks[i]) {
+                Some(ChunkBlock::Context(lines)) => &lines[lines.len().saturating_sub(context)..],
+                _ => &[],
+            };
+            let after = match chunk.blocks.get(i + 1) {
+                Some(ChunkBlock::Context(lines)) => &lines[..lines.len().min(context)],
+                _ => &[],
+            };
+
+            let old_count = (before.len() + changed.removed.len() + after.len()) as u32;
+            let new_count = (before.len() + changed.added.len() + after.len()) as u32;
+            let old_start = old_line - before.len() as u32;
+            let new_start = new_line - before.len() as u32;
+            let header = ChunkHeader {
+                old_start: old_start - u32::from(old_count == 0),
+                old_count,
+                new_start: new_start - u32::from(new_count == 0),
+                new_count,
+                annotation: chunk_header.annotation.clone(),
+            };
+            let blocks = [
+                (!before.is_empty()).then(|| ChunkBlock::Context(before.to_vec())),
+                Some(block.clone()),
+                (!after.is_empty()).then(|| ChunkBlock::Context(after.to_vec())),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            hunks.push(Hunk {
+                file_path: diff.file_name().to_owned(),
+                header,
+                blocks,
+            });
+            old_line += changed.removed.len() as u32;
+            new_line += changed.added.len() as u32;
+        }
+    }
+    hunks
+}
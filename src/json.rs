@@ -0,0 +1,144 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::heuristics::HeuristicPipeline;
+use crate::{Chunk, ChunkBlock, FileDiff, PatchSet};
+use std::fmt::Write;
+
+impl<'a> PatchSet<'a> {
+    /// Renders the patch set as a single JSON document, `{"files": [...]}`, with one object per
+    /// file as described in `to_ndjson`.
+    pub fn to_json(&self, fingerprints: Option<&HeuristicPipeline>) -> String {
+        let mut out = String::from("{\"files\":[");
+        for (i, file_diff) in self.files.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_file(&mut out, file_diff, fingerprints);
+        }
+        out.push_str("]}\n");
+        out
+    }
+
+    /// Renders the patch set as one JSON object per line and file, e.g. for streaming into other
+    /// tools: `{"file": ..., "chunks": [...]}`. Each chunk has its `header` and its `blocks`, each
+    /// of which is one of `{"context": [lines]}`, `{"removed": [lines], "added": [lines]}`, the
+    /// same with an `"elided": reason` for elided blocks or `"moved": true` for moved ones, or
+    /// `{"note": text}`.
+    ///
+    /// If `fingerprints` is set, changed and elided blocks also get a `fingerprint` computed with
+    /// that pipeline by `Changed::fingerprint`, as 16 hex digits, so consumers can group identical
+    /// changes without hashing the text again.
+    pub fn to_ndjson(&self, fingerprints: Option<&HeuristicPipeline>) -> String {
+        let mut out = String::new();
+        for file_diff in &self.files {
+            write_file(&mut out, file_diff, fingerprints);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn write_file(out: &mut String, file_diff: &FileDiff, fingerprints: Option<&HeuristicPipeline>) {
+    out.push_str("{\"file\":");
+    write_string(out, file_diff.file_name());
+    out.push_str(",\"chunks\":[");
+    for (i, chunk) in file_diff.chunks.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_chunk(out, chunk, fingerprints);
+    }
+    out.push_str("]}");
+}
+
+fn write_chunk(out: &mut String, chunk: &Chunk, fingerprints: Option<&HeuristicPipeline>) {
+    out.push_str("{\"header\":");
+    write_string(out, chunk.header.trim_end_matches(['\r', '\n']));
+    out.push_str(",\"blocks\":[");
+    for (i, block) in chunk.blocks.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let changed = match block {
+            ChunkBlock::Context(lines) => {
+                out.push_str("{\"context\":");
+                write_lines(out, lines);
+                out.push('}');
+                continue;
+            }
+            ChunkBlock::Note(note) => {
+                out.push_str("{\"note\":");
+                write_string(out, note);
+                out.push('}');
+                continue;
+            }
+            ChunkBlock::Changed(changed) => {
+                out.push('{');
+                changed
+            }
+            ChunkBlock::Moved(changed) => {
+                out.push_str("{\"moved\":true,");
+                changed
+            }
+            ChunkBlock::Elided(changed, reason) => {
+                out.push_str("{\"elided\":");
+                write_string(out, &reason.to_string());
+                out.push(',');
+                changed
+            }
+        };
+        out.push_str("\"removed\":");
+        write_lines(out, &changed.removed);
+        out.push_str(",\"added\":");
+        write_lines(out, &changed.added);
+        if let Some(pipeline) = fingerprints {
+            write!(
+                out,
+                ",\"fingerprint\":\"{:016x}\"",
+                changed.fingerprint(pipeline)
+            )
+            .unwrap();
+        }
+        out.push('}');
+    }
+    out.push_str("]}");
+}
+
+fn write_lines(out: &mut String, lines: &[&str]) {
+    out.push('[');
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, line);
+    }
+    out.push(']');
+}
+
+pub(crate) fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,1424 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use heuristics::HeuristicPipeline;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use similarity::SimilarityMetric;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+mod canonical;
+mod chunk_header;
+mod config;
+mod context;
+mod derive;
+pub mod diff_summary;
+mod file_filter;
+pub mod format_detector;
+pub mod heuristics;
+mod hunk;
+mod json;
+pub mod lint;
+mod markers;
+mod moves;
+mod noop;
+mod parser;
+mod patch_set;
+mod preview;
+pub mod profile;
+mod reference;
+mod renames;
+mod replacement;
+mod scope;
+pub mod similarity;
+mod stats;
+pub mod test_runner;
+mod well_formed;
+mod writer;
+
+pub use canonical::canonicalize;
+pub use chunk_header::{parse_chunk_header, ChunkHeader, DiffParseError, OverlappingChunks};
+pub use config::{
+    ConfigError, FilterErrorPolicy, ParseErrorPolicy, ProcessConfig, ProcessConfigBuilder,
+    ValidationWarning,
+};
+pub use derive::derive_replacements;
+pub use file_filter::{FileFilter, FileFilterError, GlobSet};
+pub use hunk::{diff_to_hunks, process_hunk, Hunk};
+pub use markers::{InvalidMarkers, Markers};
+pub use parser::{DiffParser, ParserConfig};
+pub use patch_set::PatchSet;
+pub use reference::ContextMismatch;
+pub use renames::{detect_renames, RenameCandidate};
+pub use replacement::{
+    rules_from_toml, Replacement, ReplacementMatcher, ReplacementRegistry, ReplacementSet,
+    ReplacementValidationError, RulesError,
+};
+pub use stats::{FileStats, Stats};
+pub use well_formed::{ValidationError, ValidationErrorKind};
+pub use writer::DiffWriter;
+
+#[derive(Clone, Debug)]
+pub struct FileDiff<'a> {
+    /// Everything before the first `@@` line, including the trailing newline. This is only owned
+    /// if it was rewritten.
+    pub header: Cow<'a, str>,
+    pub chunks: Vec<Chunk<'a>>,
+}
+
+impl<'a> FileDiff<'a> {
+    /// Returns the path of the changed file, without the `a/` or `b/` prefix. For deleted files,
+    /// this is the path of the file before deletion. Files without `---` and `+++` lines, e.g.
+    /// binary files, fall back to the path in the `diff --git` line.
+    pub fn file_name(&self) -> &str {
+        let path_after = |prefix: &str| {
+            self.header
+                .lines()
+                .find_map(|line| line.strip_prefix(prefix))
+                // Some tools append a tab and a timestamp or revision after the path, and some pad
+                // the path before the tab.
+                .map(|path| path.split('\t').next().unwrap_or(path).trim_end())
+                .filter(|path| *path != "/dev/null")
+        };
+        match path_after("+++ ") {
+            Some(path) => path.strip_prefix("b/").unwrap_or(path),
+            None => match path_after("--- ") {
+                Some(path) => path.strip_prefix("a/").unwrap_or(path),
+                None => self.git_path().unwrap_or_default(),
+            },
+        }
+    }
+
+    /// Returns whether `component` is one of the slash-separated parts of `file_name()`, e.g.
+    /// `test` for `foo/test/bar.cc`, but not for `foo/testing/bar.cc`.
+    pub fn contains_path_component(&self, component: &str) -> bool {
+        self.path_components().contains(&component)
+    }
+
+    /// Splits `file_name()` into its slash-separated parts, e.g. `["foo", "test", "bar.cc"]`. The
+    /// `a/` and `b/` prefixes are already stripped, and empty parts, e.g. from a doubled slash,
+    /// are skipped.
+    pub fn path_components(&self) -> Vec<&str> {
+        self.file_name()
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .collect()
+    }
+
+    /// Extracts the path after `b/` from a `diff --git a/... b/...` line.
+    fn git_path(&self) -> Option<&str> {
+        let paths = self
+            .header
+            .lines()
+            .find_map(|line| line.strip_prefix("diff --git a/"))?;
+        // A path containing ` b/` makes the line ambiguous, so prefer the split where both paths
+        // are the same, as they are for anything but renames and copies.
+        let middle = paths.len().checked_sub(" b/".len())? / 2;
+        paths
+            .get(middle..)
+            .and_then(|path| path.strip_prefix(" b/"))
+            .or_else(|| paths.rsplit_once(" b/").map(|(_, path)| path))
+    }
+
+    /// Returns the number of context lines rendered across all chunks.
+    pub fn total_context_lines(&self) -> usize {
+        self.chunks.iter().map(Chunk::context_line_count).sum()
+    }
+
+    /// Returns the number of removed and added lines in changed blocks, including moved ones, that
+    /// have not been elided.
+    pub fn total_changed_lines(&self) -> (usize, usize) {
+        self.chunks.iter().flat_map(|chunk| &chunk.blocks).fold(
+            (0, 0),
+            |(removed, added), block| match block {
+                ChunkBlock::Changed(changed) | ChunkBlock::Moved(changed) => {
+                    (removed + changed.removed.len(), added + changed.added.len())
+                }
+                _ => (removed, added),
+            },
+        )
+    }
+
+    /// Returns the `before` side of each replacement that matches the removed lines of a changed
+    /// or elided block in this file, with its `after` side occurring in the added lines, or for a
+    /// regex replacement, with a line it produces among the added lines. This
+    /// covers blocks that were kept because of other changes too. After reduction, blocks in
+    /// dropped chunks are gone, so `FileStats::matched_rules` records this for the input.
+    pub fn replaced_symbols(&self, replacements: &[Replacement]) -> HashSet<String> {
+        let blocks = self
+            .chunks
+            .iter()
+            .flat_map(|chunk| &chunk.blocks)
+            .filter_map(|block| match block {
+                ChunkBlock::Changed(changed) | ChunkBlock::Elided(changed, _) => Some(changed),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        replacements
+            .iter()
+            .filter(|replacement| {
+                blocks.iter().any(|changed| {
+                    let removed = changed.removed.join("\n");
+                    replacement.is_match(&removed)
+                        && match replacement.pattern {
+                            None => changed
+                                .added
+                                .iter()
+                                .any(|line| line.contains(&*replacement.after)),
+                            // `after` may refer to capture groups, so it cannot be searched for.
+                            Some(_) => replacement.apply(&removed).lines().any(|line| {
+                                changed.added.contains(&line) && !changed.removed.contains(&line)
+                            }),
+                        }
+                })
+            })
+            .map(|replacement| replacement.before.to_string())
+            .collect()
+    }
+
+    /// Orders the chunks by their start line in the original file, for tools that emit them out
+    /// of order. Chunks whose header cannot be parsed keep their place relative to each other at
+    /// the front. Sorting only makes sense if the chunks do not overlap, so the first overlap
+    /// found is reported, though the chunks are sorted either way.
+    pub fn sort_chunks(&mut self) -> Result<(), OverlappingChunks> {
+        self.chunks.sort_by_key(Chunk::start_line);
+        let headers = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| ChunkHeader::parse(&chunk.header))
+            .collect::<Vec<_>>();
+        match headers
+            .windows(2)
+            .find(|pair| pair[0].old_start + pair[0].old_count > pair[1].old_start)
+        {
+            Some(pair) => Err(OverlappingChunks {
+                file_name: self.file_name().to_owned(),
+                line: pair[1].old_start,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Iterates over the chunks starting between `start_line` and `end_line`, inclusive, in the
+    /// original file. Chunks whose header cannot be parsed are skipped.
+    pub fn chunks_in_range(
+        &self,
+        start_line: u32,
+        end_line: u32,
+    ) -> impl Iterator<Item = &Chunk<'a>> {
+        self.chunks.iter().filter(move |chunk| {
+            chunk
+                .start_line()
+                .is_some_and(|line| (start_line..=end_line).contains(&line))
+        })
+    }
+
+    /// Returns a copy with only the changed blocks, moved or not, e.g. to compare the changes
+    /// themselves regardless of where they are. Chunks left without changes are dropped, and the
+    /// counts in the remaining `@@` headers are recomputed; the start lines are kept. The result
+    /// does not apply, since the changes lose the context between them.
+    pub fn extract_changed_only(&self) -> FileDiff<'a> {
+        let chunks = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| {
+                let blocks = chunk
+                    .blocks
+                    .iter()
+                    .filter(|block| matches!(block, ChunkBlock::Changed(_) | ChunkBlock::Moved(_)))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if blocks.is_empty() {
+                    return None;
+                }
+                let header = match ChunkHeader::parse(&chunk.header) {
+                    Some(header) => Cow::Owned(format!("{}\n", header.update_from_blocks(&blocks))),
+                    None => chunk.header.clone(),
+                };
+                Some(Chunk { header, blocks })
+            })
+            .collect();
+        FileDiff {
+            header: self.header.clone(),
+            chunks,
+        }
+    }
+}
+
+impl<'a> FileDiff<'a> {
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, markers: &Markers) -> fmt::Result {
+        write!(f, "{}", self.header)?;
+        for chunk in &self.chunks {
+            chunk.fmt_with(f, markers)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for FileDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &Markers::UNIFIED)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Chunk<'a> {
+    /// The `@@` line, including the trailing newline. This is only owned if it was recomputed.
+    pub header: Cow<'a, str>,
+    pub blocks: Vec<ChunkBlock<'a>>,
+}
+
+impl<'a> Chunk<'a> {
+    /// Returns the first line in the original file covered by this chunk, if the header can be
+    /// parsed.
+    pub fn start_line(&self) -> Option<u32> {
+        ChunkHeader::parse(&self.header).map(|header| header.old_start)
+    }
+
+    /// Returns the text after the closing `@@` of the header, usually the enclosing function, e.g.
+    /// `MyClass::MyMethod` for `@@ -1,5 +1,5 @@ MyClass::MyMethod`. This is the same as
+    /// `ChunkHeader::annotation`, without parsing or copying the header.
+    pub fn annotation_string(&self) -> Option<&str> {
+        let (_, annotation) = self.header.strip_prefix("@@ ")?.split_once(" @@")?;
+        Some(annotation.trim()).filter(|annotation| !annotation.is_empty())
+    }
+
+    /// Iterates over the blocks of the given kind, e.g. only the `Changed` ones.
+    pub fn blocks_of_kind(&self, kind: ChunkBlockKind) -> impl Iterator<Item = &ChunkBlock<'a>> {
+        self.blocks.iter().filter(move |block| block.kind() == kind)
+    }
+
+    /// Returns whether any changed block, moved or not, survives in this chunk.
+    pub fn has_changes(&self) -> bool {
+        self.blocks_of_kind(ChunkBlockKind::Changed)
+            .next()
+            .is_some()
+            || self.blocks_of_kind(ChunkBlockKind::Moved).next().is_some()
+    }
+
+    /// Returns whether this chunk would only render unchanged lines, e.g. once every changed block
+    /// in it has been elided.
+    pub fn has_pure_context(&self) -> bool {
+        !self.has_changes()
+    }
+
+    /// Returns the number of unchanged lines in this chunk. Elided blocks and notes are not
+    /// counted, since they are not rendered as context.
+    pub fn context_line_count(&self) -> usize {
+        self.blocks
+            .iter()
+            .filter_map(ChunkBlock::as_context)
+            .map(Vec::len)
+            .sum()
+    }
+
+    /// Returns the share of changed lines among the rendered lines of this chunk, from 0 to 1:
+    /// removed and added lines of `Changed` and `Moved` blocks, over those plus context lines. A
+    /// chunk without any such lines has a ratio of 0.
+    pub fn change_ratio(&self) -> f64 {
+        let changed = self
+            .blocks
+            .iter()
+            .map(|block| match block {
+                ChunkBlock::Changed(changed) | ChunkBlock::Moved(changed) => {
+                    changed.removed.len() + changed.added.len()
+                }
+                _ => 0,
+            })
+            .sum::<usize>();
+        let total = changed + self.context_line_count();
+        if total == 0 {
+            0.0
+        } else {
+            changed as f64 / total as f64
+        }
+    }
+
+    /// Returns the number of lines in the blocks of this chunk, as counted by
+    /// `ChunkBlock::line_count`: both sides of changes, elided or not, and context.
+    pub fn total_raw_lines(&self) -> usize {
+        self.blocks.iter().map(ChunkBlock::line_count).sum()
+    }
+
+    /// Returns the number of lines this chunk covers in the original file, i.e. context plus
+    /// removed lines, which is what the old count in an accurate header says. Elided blocks count
+    /// as the changes they were, and notes do not count.
+    pub fn old_side_line_count(&self) -> usize {
+        self.side_line_count(|changed| changed.removed.len())
+    }
+
+    /// Like `old_side_line_count`, but for the new file: context plus added lines.
+    pub fn new_side_line_count(&self) -> usize {
+        self.side_line_count(|changed| changed.added.len())
+    }
+
+    fn side_line_count(&self, changed_lines: impl Fn(&Changed) -> usize) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                ChunkBlock::Context(lines) => lines.len(),
+                ChunkBlock::Changed(changed)
+                | ChunkBlock::Elided(changed, _)
+                | ChunkBlock::Moved(changed) => changed_lines(changed),
+                ChunkBlock::Note(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Renders this chunk as a self-contained diff of `file_name`, with synthesized `--- a/` and
+    /// `+++ b/` headers. The `@@` header is used as is, so unless context was trimmed to recompute
+    /// it, the patch only applies if nothing in the chunk was elided.
+    pub fn to_standalone_patch(&self, file_name: &str) -> String {
+        format!("--- a/{file_name}\n+++ b/{file_name}\n{self}")
+    }
+}
+
+impl<'a> Chunk<'a> {
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, markers: &Markers) -> fmt::Result {
+        write!(f, "{}", self.header)?;
+        for block in &self.blocks {
+            block.fmt_with(f, markers)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Chunk<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &Markers::UNIFIED)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ChunkBlock<'a> {
+    Context(Vec<&'a str>),
+    Changed(Changed<'a>),
+    /// A changed block that was determined to be mechanical. It is not rendered, but is kept
+    /// around so later passes know what the original lines were.
+    Elided(Changed<'a>, ElisionReason),
+    /// Text added by diffreducer itself, rendered as a context line. It does not correspond to
+    /// anything in the original file, so a diff containing notes will not apply.
+    Note(String),
+    /// A changed block whose lines were found on the other side of another block in the patch
+    /// set, e.g. code moved to another file. See `PatchSet::detect_moves`.
+    Moved(Changed<'a>),
+}
+
+/// Which variant a `ChunkBlock` is, without its contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChunkBlockKind {
+    Context,
+    Changed,
+    Elided,
+    Note,
+    Moved,
+}
+
+impl<'a> ChunkBlock<'a> {
+    pub fn kind(&self) -> ChunkBlockKind {
+        match self {
+            ChunkBlock::Context(_) => ChunkBlockKind::Context,
+            ChunkBlock::Changed(_) => ChunkBlockKind::Changed,
+            ChunkBlock::Elided(..) => ChunkBlockKind::Elided,
+            ChunkBlock::Note(_) => ChunkBlockKind::Note,
+            ChunkBlock::Moved(_) => ChunkBlockKind::Moved,
+        }
+    }
+
+    /// Returns the lines of a `Context` block.
+    pub fn as_context(&self) -> Option<&Vec<&'a str>> {
+        match self {
+            ChunkBlock::Context(lines) => Some(lines),
+            _ => None,
+        }
+    }
+
+    /// Returns the lines of a `Changed` block. Elided and moved blocks are not included.
+    pub fn as_changed(&self) -> Option<&Changed<'a>> {
+        match self {
+            ChunkBlock::Changed(changed) => Some(changed),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of diff lines in this block: context lines, plus removed and added
+    /// lines for changes, including elided ones. Notes are not part of the original diff, so they
+    /// count as none.
+    pub fn line_count(&self) -> usize {
+        match self {
+            ChunkBlock::Context(lines) => lines.len(),
+            ChunkBlock::Changed(changed)
+            | ChunkBlock::Elided(changed, _)
+            | ChunkBlock::Moved(changed) => changed.removed.len() + changed.added.len(),
+            ChunkBlock::Note(_) => 0,
+        }
+    }
+
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, markers: &Markers) -> fmt::Result {
+        match self {
+            ChunkBlock::Context(lines) => {
+                for line in lines {
+                    writeln!(f, "{}{line}", markers.context)?;
+                }
+            }
+            ChunkBlock::Changed(changed) => {
+                changed.fmt_with(f, markers)?;
+            }
+            ChunkBlock::Elided(..) => (),
+            ChunkBlock::Note(note) => {
+                writeln!(f, "{}{note}", markers.context)?;
+            }
+            ChunkBlock::Moved(changed) => {
+                changed.fmt_with(
+                    f,
+                    &Markers {
+                        removed: markers.moved_removed,
+                        added: markers.moved_added,
+                        ..markers.clone()
+                    },
+                )?;
+            }
+        };
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for ChunkBlock<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &Markers::UNIFIED)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Changed<'a> {
+    pub removed: Vec<&'a str>,
+    pub added: Vec<&'a str>,
+}
+
+impl<'a> Changed<'a> {
+    /// Returns whether the block only adds lines.
+    pub fn is_pure_addition(&self) -> bool {
+        self.removed.is_empty() && !self.added.is_empty()
+    }
+
+    /// Returns whether the block only removes lines.
+    pub fn is_pure_deletion(&self) -> bool {
+        self.added.is_empty() && !self.removed.is_empty()
+    }
+
+    /// Returns whether the block has no lines at all, which the parser never produces.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    /// Returns whether both sides consist of exactly one whitespace-delimited token, e.g. when a
+    /// function is renamed on a line of its own. Pure additions and removals never qualify.
+    pub fn is_single_token_change(&self) -> bool {
+        fn is_single_token(lines: &[&str]) -> bool {
+            let mut tokens = lines.iter().flat_map(|line| line.split_whitespace());
+            tokens.next().is_some() && tokens.next().is_none()
+        }
+        is_single_token(&self.removed) && is_single_token(&self.added)
+    }
+
+    /// Returns whether both sides consist only of comment lines, i.e. lines starting with `//`,
+    /// `#`, `/*`, `*/` or `*` after indentation, whose text is the same up to whitespace once the
+    /// comment delimiters, including a trailing `*/`, are removed. This is the case when a comment
+    /// is rewrapped. Pure additions and removals never qualify.
+    pub fn is_comment_reflow(&self) -> bool {
+        fn comment_words<'a>(lines: &[&'a str]) -> Option<Vec<&'a str>> {
+            let mut words = Vec::new();
+            for line in lines {
+                let line = line.trim_start();
+                let text = ["//", "/*", "*/", "*", "#"]
+                    .iter()
+                    .find_map(|delimiter| line.strip_prefix(delimiter))?;
+                let text = text.trim_end();
+                words.extend(text.strip_suffix("*/").unwrap_or(text).split_whitespace());
+            }
+            Some(words)
+        }
+        !self.removed.is_empty()
+            && !self.added.is_empty()
+            && matches!(
+                (comment_words(&self.removed), comment_words(&self.added)),
+                (Some(removed), Some(added)) if removed == added
+            )
+    }
+
+    /// Returns the number of lines that have to be inserted, deleted or substituted to turn the
+    /// removed lines into the added ones. Lines are compared exactly.
+    pub fn levenshtein_distance(&self) -> usize {
+        similarity::levenshtein(&self.removed, &self.added)
+    }
+
+    /// Applies just `replacement` to the removed lines, and returns the result as a block if the
+    /// replacement matched and produced exactly the added lines, e.g. to show which rule accounts
+    /// for a block. Unlike `evaluate`, nothing is normalized and no other rule is tried.
+    pub fn try_apply_single_replacement(&self, replacement: &Replacement) -> Option<OwnedChanged> {
+        let removed = self.removed.join("\n");
+        if !replacement.is_match(&removed) {
+            return None;
+        }
+        let replaced = replacement.apply(&removed);
+        let added = replaced.split('\n').collect::<Vec<_>>();
+        (added == self.added).then(|| {
+            Changed {
+                removed: self.removed.clone(),
+                added,
+            }
+            .into_owned()
+        })
+    }
+
+    /// Returns a hash of both sides after normalizing them with `pipeline`, so blocks that only
+    /// differ in ways the pipeline ignores get the same fingerprint. The hash is FNV-1a, which is
+    /// fast and stable across releases and platforms, so fingerprints can be stored and compared
+    /// later. It is not collision resistant.
+    pub fn fingerprint(&self, pipeline: &HeuristicPipeline) -> u64 {
+        let removed = pipeline.apply(&self.removed);
+        let added = pipeline.apply(&self.added);
+        // Hashing the length of the removed side keeps e.g. ("ab", "c") apart from ("a", "bc").
+        [
+            &(removed.len() as u64).to_le_bytes(),
+            removed.as_bytes(),
+            added.as_bytes(),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+impl Changed<'_> {
+    /// Builds a changed block from owned lines, e.g. in tests or when the lines were generated
+    /// rather than parsed. A `Changed` only borrows its lines, so this returns an `OwnedChanged`
+    /// to hold them; use `OwnedChanged::as_changed` to get a `Changed` back.
+    pub fn from_lines(removed: Vec<String>, added: Vec<String>) -> OwnedChanged {
+        OwnedChanged { removed, added }
+    }
+
+    /// Copies the lines, so the block no longer borrows from the input.
+    pub fn into_owned(self) -> OwnedChanged {
+        OwnedChanged {
+            removed: self.removed.into_iter().map(str::to_owned).collect(),
+            added: self.added.into_iter().map(str::to_owned).collect(),
+        }
+    }
+}
+
+/// A `Changed` that owns its lines, so it does not need to borrow from the input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OwnedChanged {
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+impl OwnedChanged {
+    /// Borrows the lines as a `Changed`, to use with anything that expects one.
+    pub fn as_changed(&self) -> Changed<'_> {
+        Changed {
+            removed: self.removed.iter().map(String::as_str).collect(),
+            added: self.added.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl<'a> From<Changed<'a>> for OwnedChanged {
+    fn from(changed: Changed<'a>) -> Self {
+        changed.into_owned()
+    }
+}
+
+impl fmt::Display for OwnedChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_changed().fmt(f)
+    }
+}
+
+impl<'a> Changed<'a> {
+    pub(crate) fn fmt_with(&self, f: &mut fmt::Formatter<'_>, markers: &Markers) -> fmt::Result {
+        for line in &self.removed {
+            writeln!(f, "{}{line}", markers.removed)?;
+        }
+        for line in &self.added {
+            writeln!(f, "{}{line}", markers.added)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Changed<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &Markers::UNIFIED)
+    }
+}
+
+/// Builds an error for `line`, which must be a slice of `input`, locating it by its offset.
+fn parse_error(input: &str, line: &str, message: impl Into<String>) -> DiffParseError {
+    let offset = line.as_ptr() as usize - input.as_ptr() as usize;
+    DiffParseError {
+        line: line.trim_end_matches(['\r', '\n']).to_owned(),
+        message: message.into(),
+        line_number: Some(input[..offset].matches('\n').count() + 1),
+        byte_offset: Some(offset),
+    }
+}
+
+/// Splits the body of a chunk into its line prefixes and lines. If the header can be parsed, the
+/// body ends once it has as many lines as the header says, so whatever follows the last chunk of a
+/// file, e.g. the blank line between files in our own output or the `diff -r` command line before
+/// the next file, is not taken for part of it. Within those lines, an empty line is a context line
+/// whose leading space was stripped, e.g. by an editor.
+///
+/// Otherwise, or if a line does not fit the counts, the body ends at the first line that does
+/// not look like part of a chunk. Problems are added to `errors`; `header` and `body` must be
+/// slices of `input` to locate them.
+///
+/// A `\ No newline at end of file` marker is dropped, unless `keep_no_newline_markers` is set, in
+/// which case it is kept as part of the line before it, so it is rendered again right after that
+/// line.
+fn chunk_lines<'a>(
+    input: &str,
+    header: &str,
+    body: &'a str,
+    keep_no_newline_markers: bool,
+    errors: &mut Vec<DiffParseError>,
+) -> Vec<(&'a str, &'a str)> {
+    let counts = match parse_chunk_header(header) {
+        Ok(header) => Some((header.old_count, header.new_count)),
+        Err(error) => {
+            errors.push(parse_error(input, header, error.message));
+            None
+        }
+    };
+    let (mut old_lines, mut new_lines) = counts.unwrap_or((u32::MAX, u32::MAX));
+    let mut lines: Vec<(&str, &str)> = Vec::new();
+    for line in body.lines() {
+        // A `\ No newline at end of file` marker follows the line it refers to. Its text depends
+        // on the locale, so only the backslash is checked.
+        if old_lines == 0 && new_lines == 0 && !line.starts_with('\\') {
+            if line.starts_with([' ', '-', '+']) {
+                errors.push(parse_error(
+                    input,
+                    line,
+                    "the chunk has more lines than its header says",
+                ));
+            }
+            break;
+        }
+        if line.starts_with('\\') {
+            if let Some((_, previous)) = lines.last_mut().filter(|_| keep_no_newline_markers) {
+                // Both are slices of `body`, so the marker is appended by widening the slice.
+                let start = previous.as_ptr() as usize - body.as_ptr() as usize;
+                let end = line.as_ptr() as usize - body.as_ptr() as usize + line.len();
+                *previous = &body[start..end];
+            }
+            continue;
+        }
+        let (prefix, text) = match line {
+            "" => (" ", ""),
+            _ if line.starts_with([' ', '-', '+']) => line.split_at(1),
+            _ => {
+                if counts.is_some() {
+                    errors.push(parse_error(
+                        input,
+                        line,
+                        "expected a line starting with ' ', '-', '+' or '\\'",
+                    ));
+                }
+                break;
+            }
+        };
+        let fits = match prefix {
+            " " => old_lines > 0 && new_lines > 0,
+            "-" => old_lines > 0,
+            "+" => new_lines > 0,
+            _ => true,
+        };
+        // Keep the line anyway, in case the header is what is wrong.
+        if !fits {
+            errors.push(parse_error(
+                input,
+                line,
+                "the chunk has more lines than its header says",
+            ));
+        }
+        match prefix {
+            " " => {
+                old_lines = old_lines.saturating_sub(1);
+                new_lines = new_lines.saturating_sub(1);
+            }
+            "-" => old_lines = old_lines.saturating_sub(1),
+            "+" => new_lines = new_lines.saturating_sub(1),
+            _ => (),
+        }
+        lines.push((prefix, text));
+    }
+    if counts.is_some() && (old_lines > 0 || new_lines > 0) {
+        errors.push(parse_error(
+            input,
+            header,
+            "the chunk has fewer lines than its header says",
+        ));
+    }
+    lines
+}
+
+/// Splits a unified diff into per-file diffs.
+///
+/// Anything before the first file header, such as the summary printed by `git diff --stat -p` or a
+/// commit message, is skipped. In particular, a diffstat is not carried over, since its counts
+/// would no longer match once changes are elided.
+///
+/// Malformed input is parsed as well as possible; see `find_parse_errors` to check for it.
+pub fn parse_file_diffs(input: &str) -> Vec<FileDiff<'_>> {
+    parse(input, &ParserConfig::default(), &mut Vec::new())
+}
+
+/// Like `parse_file_diffs`, but handles malformed input according to `policy`. With
+/// `ParseErrorPolicy::Fail`, the first problem `find_parse_errors` would report is returned.
+pub fn parse_file_diffs_with_policy(
+    input: &str,
+    policy: ParseErrorPolicy,
+) -> Result<Vec<FileDiff<'_>>, DiffParseError> {
+    DiffParser::with_config(ParserConfig {
+        error_policy: policy,
+        ..ParserConfig::default()
+    })
+    .parse(input)
+}
+
+/// Returns every problem found while parsing `input` as a unified diff, e.g. a malformed `@@`
+/// line or a chunk with fewer lines than its header says. Input without any file header is only
+/// an error if it is not blank, so an empty diff is valid.
+pub fn find_parse_errors(input: &str) -> Vec<DiffParseError> {
+    let mut errors = Vec::new();
+    let file_diffs = parse(input, &ParserConfig::default(), &mut errors);
+    check_file_headers(input, &file_diffs, &mut errors);
+    errors
+}
+
+pub(crate) fn check_file_headers(
+    input: &str,
+    file_diffs: &[FileDiff],
+    errors: &mut Vec<DiffParseError>,
+) {
+    if file_diffs.is_empty() {
+        if let Some(line) = input.lines().find(|line| !line.trim().is_empty()) {
+            errors.push(parse_error(input, line, "no file header found"));
+        }
+    }
+}
+
+/// Parses `input`, adding any problems found to `errors`. With `ParseErrorPolicy::Warn`, chunks
+/// with problems are dropped, along with any file left without chunks.
+pub(crate) fn parse<'a>(
+    input: &'a str,
+    config: &ParserConfig,
+    errors: &mut Vec<DiffParseError>,
+) -> Vec<FileDiff<'a>> {
+    let skip_malformed_chunks = config.error_policy == ParseErrorPolicy::Warn;
+    // diff --git a/ash/accelerators/accelerator_capslock_state_machine.cc b/ash/accelerators/accelerator_capslock_state_machine.cc
+    // index 28c373b242560..75f0f75e738a2 100644
+    // --- a/ash/accelerators/accelerator_capslock_state_machine.cc
+    // +++ b/ash/accelerators/accelerator_capslock_state_machine.cc
+    //
+    // where the hashes may be abbreviated to any length and the mode may be missing, e.g. if it
+    // changed and is reported on separate lines. The `index` line may be preceded by other extended
+    // headers, such as `new file mode 100644`. Files without textual changes, e.g. binary files,
+    // have no `---` and `+++` lines at all; everything up to the next file is kept as their header,
+    // so a `GIT binary patch` is passed through verbatim,
+    //
+    // or, from `svn diff`:
+    //
+    // Index: trunk/src/foo.c
+    // ===================================================================
+    // --- trunk/src/foo.c	(revision 1234)
+    // +++ trunk/src/foo.c	(working copy)
+    static FILE_HEADER_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"(?m)",
+            r"^(?:diff --git a/.+ b/.+\n",
+            r"(?:(?:(?:old|new|deleted file|new file) mode|copy from|copy to|rename from|rename to",
+            r"|(?:dis)?similarity index|index) .+\n)*",
+            r"(?:--- .+\n[+]{3} .+\n)?",
+            r"|(?:Index: .+\n=+\n)?--- .+\n[+]{3} .+\n)",
+        ))
+        .unwrap()
+    });
+    // @@ -27,8 +27,8 @@ AcceleratorCapslockStateMachine::AcceleratorCapslockStateMachine(
+    static CHUNK_HEADER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^@@ .+\n").unwrap());
+
+    let file_headers = FILE_HEADER_RE
+        .find_iter(input)
+        .map(Some)
+        .chain(Some(None))
+        .collect::<Vec<_>>();
+
+    file_headers
+        .iter()
+        .zip(file_headers.iter().skip(1))
+        .take(config.max_file_diffs.unwrap_or(usize::MAX))
+        .filter_map(|(current, next)| {
+            // By construction, there should always be a `current`.
+            let current = current.unwrap();
+            let header = current.as_str();
+
+            let file_diff_text = match next {
+                Some(next) => &input[current.end()..next.start()],
+                None => &input[current.start()..],
+            };
+
+            let chunk_headers = CHUNK_HEADER_RE
+                .find_iter(file_diff_text)
+                .map(Some)
+                .chain(Some(None))
+                .collect::<Vec<_>>();
+            if chunk_headers.len() == 1 {
+                let end = next.map_or(input.len(), |next| next.start());
+                return Some(FileDiff {
+                    header: Cow::Borrowed(&input[current.start()..end]),
+                    chunks: Vec::new(),
+                });
+            }
+
+            let chunks = chunk_headers
+                .iter()
+                .zip(chunk_headers.iter().skip(1))
+                .filter_map(|(current, next)| {
+                    // By construction, there should always be a `current`.
+                    let current = current.unwrap();
+                    let header = current.as_str();
+
+                    let chunk_text = match next {
+                        Some(next) => &file_diff_text[current.end()..next.start()],
+                        None => &file_diff_text[current.end()..],
+                    };
+
+                    let error_count = errors.len();
+                    let chunk_text_lines = chunk_lines(
+                        input,
+                        header,
+                        chunk_text,
+                        config.preserve_no_newline_markers,
+                        errors,
+                    );
+                    if skip_malformed_chunks && errors.len() > error_count {
+                        return None;
+                    }
+                    // Any contiguous run of removed and added lines forms a single changed block,
+                    // regardless of how they are interleaved: a reflow can easily produce `-+-+`.
+                    let is_change = |prefix| prefix == "-" || prefix == "+";
+                    let blocks = chunk_text_lines
+                        .chunk_by(|&(a, _), &(b, _)| a == b || is_change(a) && is_change(b))
+                        .map(|lines| {
+                            let (removed, added) = lines.iter().fold(
+                                (Vec::new(), Vec::new()),
+                                |(mut removed, mut added), &(prefix, line)| {
+                                    match prefix {
+                                        " " => (),
+                                        "-" => removed.push(line),
+                                        "+" => added.push(line),
+                                        _ => (),
+                                    };
+                                    (removed, added)
+                                },
+                            );
+                            if removed.is_empty() && added.is_empty() {
+                                ChunkBlock::Context(
+                                    lines.iter().map(|(_prefix, line)| line).copied().collect(),
+                                )
+                            } else {
+                                ChunkBlock::Changed(Changed { removed, added })
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    Some(Chunk {
+                        header: Cow::Borrowed(header),
+                        blocks,
+                    })
+                })
+                .collect::<Vec<_>>();
+            if chunks.is_empty() {
+                return None;
+            }
+
+            Some(FileDiff {
+                header: Cow::Borrowed(header),
+                chunks,
+            })
+        })
+        .collect()
+}
+
+pub fn process_file_diffs<'a>(
+    file_diffs: Vec<FileDiff<'a>>,
+    config: &ProcessConfig,
+) -> Vec<FileDiff<'a>> {
+    process_file_diffs_with_stats(file_diffs, config, &mut Stats::default())
+}
+
+/// Like `process_file_diffs`, but also accumulates counters about what was elided into `stats`.
+pub fn process_file_diffs_with_stats<'a>(
+    file_diffs: Vec<FileDiff<'a>>,
+    config: &ProcessConfig,
+    stats: &mut Stats,
+) -> Vec<FileDiff<'a>> {
+    let file_diffs = match config.rename_detection_threshold {
+        Some(threshold) => renames::collapse_renames(file_diffs, threshold),
+        None => file_diffs,
+    };
+    file_diffs
+        .into_iter()
+        .filter_map(|file_diff| {
+            stats.files += 1;
+            stats.chunks += file_diff.chunks.len();
+            let file_name = file_diff.file_name().to_owned();
+            let mut matched_rules = file_diff
+                .replaced_symbols(&config.replacements)
+                .into_iter()
+                .collect::<Vec<_>>();
+            matched_rules.sort();
+            if config
+                .file_filter
+                .as_ref()
+                .is_some_and(|filter| !filter.matches(&file_name))
+            {
+                stats.per_file.push(FileStats {
+                    file_name,
+                    matched_rules,
+                    ..FileStats::default()
+                });
+                return None;
+            }
+            // Files without chunks, e.g. binary files or mode changes, have nothing to reduce. Nor
+            // do added or deleted files: there is no other side to compare their lines to.
+            let mut pass_through = file_diff.chunks.is_empty()
+                || file_diff.is_new_file()
+                || file_diff.is_deleted_file();
+            if let Some(max_file_changes) = config.max_file_changes {
+                let (removed, added) = file_diff.total_changed_lines();
+                if removed + added > max_file_changes {
+                    log::debug!(
+                        "{file_name}: {} changed lines exceed max_file_changes ({max_file_changes}); \
+                         passing it through unchanged",
+                        removed + added
+                    );
+                    pass_through = true;
+                }
+            }
+            if config.require_context_match && !pass_through {
+                if let Some(reference_dir) = &config.reference_dir {
+                    let path = reference_dir.join(&file_name);
+                    // A missing file, e.g. one the diff adds, only matches a diff without context.
+                    let original = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+                        log::debug!("failed to read {}: {error}", path.display());
+                        String::new()
+                    });
+                    let mismatches = file_diff.context_mismatches(&original);
+                    if let Some(mismatch) = mismatches.into_iter().next() {
+                        stats.context_mismatches.push(mismatch);
+                        pass_through = true;
+                    }
+                }
+            }
+            if pass_through {
+                let changed_blocks = file_diff
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.blocks_of_kind(ChunkBlockKind::Changed).count())
+                    .sum();
+                stats.changed_blocks += changed_blocks;
+                stats.per_file.push(FileStats {
+                    file_name,
+                    changed_blocks,
+                    elided_blocks: 0,
+                    kept: true,
+                    matched_rules,
+                });
+                stats.record_output(&file_diff);
+                return Some(file_diff);
+            }
+            let (changed_blocks, elided_blocks) = (stats.changed_blocks, stats.elided_blocks);
+            let FileDiff { header, chunks } = file_diff;
+            let mut chunks = process_chunks(chunks, config, stats);
+            let mut file_stats = FileStats {
+                file_name,
+                changed_blocks: stats.changed_blocks - changed_blocks,
+                elided_blocks: stats.elided_blocks - elided_blocks,
+                kept: false,
+                matched_rules,
+            };
+            let reduced = chunks.iter().any(|chunk| {
+                chunk
+                    .blocks_of_kind(ChunkBlockKind::Elided)
+                    .next()
+                    .is_some()
+            });
+            // Unless `context_lines` is set, the filtered diff here may not actually apply to the
+            // original files. A given chunk may have multiple changed blocks, but the filtering
+            // mechanism used here does not restore those to "not changed" lines; it just drops
+            // them. This means that there may be context lines that don't correspond to anything.
+            // Oh well :)
+            chunks.retain(|chunk| !chunk.has_pure_context());
+            let file_diff = FileDiff { header, chunks };
+            let (removed, added) = file_diff.total_changed_lines();
+            // A rename or copy still has to happen for the rest of the diff to apply, even once
+            // all of its changes are elided.
+            if file_diff.chunks.is_empty()
+                && !config.include_unchanged_files
+                && !file_diff.is_rename_or_copy()
+                || config.only_reduced_files && !reduced
+                || config
+                    .min_file_changes
+                    .is_some_and(|min_file_changes| removed + added < min_file_changes)
+            {
+                stats.per_file.push(file_stats);
+                None
+            } else {
+                let file_diff = match config.context_lines {
+                    Some(context_lines) => context::trim_context(file_diff, context_lines),
+                    None => file_diff,
+                };
+                let file_diff = match config.context_lines {
+                    None if config.annotate_elisions => {
+                        preview::annotate_elisions(file_diff, config.annotate_elision_reasons)
+                    }
+                    _ => file_diff,
+                };
+                let file_diff = match config.keep_first {
+                    Some(keep) => preview::keep_first_chunks(file_diff, keep),
+                    None => file_diff,
+                };
+                stats.record_output(&file_diff);
+                file_stats.kept = true;
+                stats.per_file.push(file_stats);
+                Some(file_diff)
+            }
+        })
+        .collect()
+}
+
+/// Like `process_file_diffs`, but keeps every file and chunk, even once all of its changes have
+/// been elided. Each file comes with one flag per chunk, which is `true` if the chunk still has
+/// changes and `false` if it is now pure context.
+///
+/// Since nothing is pruned or re-split, `context_lines`, `keep_first`, `only_reduced_files` and
+/// `min_file_changes` have no effect here.
+pub fn process_file_diffs_preserving_context<'a>(
+    file_diffs: Vec<FileDiff<'a>>,
+    config: &ProcessConfig,
+) -> Vec<(FileDiff<'a>, Vec<bool>)> {
+    let mut stats = Stats::default();
+    file_diffs
+        .into_iter()
+        .map(|FileDiff { header, chunks }| {
+            let chunks = process_chunks(chunks, config, &mut stats);
+            let changed = chunks.iter().map(Chunk::has_changes).collect();
+            (FileDiff { header, chunks }, changed)
+        })
+        .collect()
+}
+
+/// Decides the fate of every changed block in one file's chunks, without dropping anything.
+fn process_chunks<'a>(
+    mut chunks: Vec<Chunk<'a>>,
+    config: &ProcessConfig,
+    stats: &mut Stats,
+) -> Vec<Chunk<'a>> {
+    if config.elide_scope_wrappers {
+        scope::elide_scope_wrappers(&mut chunks, stats);
+    }
+    let mut chunks = chunks
+        .into_iter()
+        .map(|Chunk { header, blocks }| {
+            let mut new_blocks = Vec::with_capacity(blocks.len());
+            let mut blocks = blocks.into_iter().peekable();
+            while let Some(block) = blocks.next() {
+                new_blocks.push(match block {
+                    ChunkBlock::Changed(changed) => {
+                        process_changed_block(changed, blocks.peek(), config, stats)
+                    }
+                    _ => block,
+                });
+            }
+            Chunk {
+                header,
+                blocks: new_blocks,
+            }
+        })
+        .collect::<Vec<_>>();
+    if config.elide_net_noops {
+        noop::elide_net_noops(&mut chunks, config, stats);
+    }
+    if let Some(min_change_ratio) = config.min_change_ratio {
+        for chunk in chunks
+            .iter_mut()
+            .filter(|chunk| chunk.change_ratio() < min_change_ratio)
+        {
+            for block in &mut chunk.blocks {
+                if let ChunkBlock::Changed(changed) =
+                    mem::replace(block, ChunkBlock::Context(Vec::new()))
+                {
+                    stats.record_elision(&ElisionReason::LowChangeRatio, &[]);
+                    *block = ChunkBlock::Elided(changed, ElisionReason::LowChangeRatio);
+                }
+            }
+        }
+    }
+    chunks
+}
+
+fn process_changed_block<'a>(
+    changed: Changed<'a>,
+    next: Option<&ChunkBlock>,
+    config: &ProcessConfig,
+    stats: &mut Stats,
+) -> ChunkBlock<'a> {
+    stats.changed_blocks += 1;
+    if config.elide_identical_removed_added && changed.removed == changed.added {
+        stats.record_elision(&ElisionReason::Identical, &[]);
+        return ChunkBlock::Elided(changed, ElisionReason::Identical);
+    }
+    // An added attribute can only be judged if the declaration it applies to is visible as an
+    // unchanged line right after it.
+    if changed.is_pure_addition()
+        && matches!(next, Some(ChunkBlock::Context(_)))
+        && filter_elides(
+            &changed.added,
+            &config.attribute_patterns,
+            &config.rejected_attribute_patterns,
+            config.on_filter_error,
+        )
+    {
+        stats.record_elision(&ElisionReason::AttributeAddition, &[]);
+        return ChunkBlock::Elided(changed, ElisionReason::AttributeAddition);
+    }
+    if changed.is_pure_addition()
+        && filter_elides(
+            &changed.added,
+            &config.suppression_patterns,
+            &config.rejected_suppression_patterns,
+            config.on_filter_error,
+        )
+    {
+        stats.record_elision(&ElisionReason::Suppression, &[]);
+        return ChunkBlock::Elided(changed, ElisionReason::Suppression);
+    }
+    match evaluate(&changed.removed, &changed.added, config) {
+        (Decision::Keep, _) => ChunkBlock::Changed(changed),
+        (Decision::Elide(reason), replacements) => {
+            stats.record_elision(&reason, &replacements);
+            ChunkBlock::Elided(changed, reason)
+        }
+    }
+}
+
+/// Returns whether every line in `lines` matches one of `patterns`. If not, and some patterns
+/// were rejected, they might have matched the rest, so `policy` decides.
+fn filter_elides(
+    lines: &[&str],
+    patterns: &[Regex],
+    rejected_patterns: &[String],
+    policy: FilterErrorPolicy,
+) -> bool {
+    if !lines.is_empty()
+        && lines
+            .iter()
+            .all(|line| patterns.iter().any(|pattern| pattern.is_match(line)))
+    {
+        return true;
+    }
+    match (rejected_patterns.first(), policy) {
+        (None, _) => false,
+        (Some(_), FilterErrorPolicy::Ignore) => true,
+        (Some(pattern), FilterErrorPolicy::Warn | FilterErrorPolicy::Fail) => {
+            log::warn!("keeping a block that pattern {pattern:?} could not be checked against");
+            false
+        }
+    }
+}
+
+/// Why a changed block was considered mechanical.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ElisionReason {
+    /// The removed and added lines only differ in whitespace or comment reflow.
+    Whitespace,
+    /// Applying the configured replacements to the removed lines produces the added lines.
+    Replacement,
+    /// Only attributes matching `ProcessConfig::attribute_patterns` were added.
+    AttributeAddition,
+    /// Only lines opening or closing a namespace scope were added, with the braces balanced
+    /// within the file.
+    ScopeWrapper,
+    /// After normalization and replacements, the removed and added lines are not identical but
+    /// at least `ProcessConfig::similarity_threshold` similar.
+    Similar,
+    /// Another changed block in the same file undoes this one, e.g. a change reverted in a later
+    /// hunk.
+    NetNoop,
+    /// Only lines matching `ProcessConfig::suppression_patterns` were added.
+    Suppression,
+    /// The removed and added lines are byte-for-byte identical, as some generators emit. See
+    /// `ProcessConfig::elide_identical_removed_added`.
+    Identical,
+    /// The changes left in the chunk make up less than `ProcessConfig::min_change_ratio` of its
+    /// lines.
+    LowChangeRatio,
+}
+
+impl fmt::Display for ElisionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElisionReason::Whitespace => write!(f, "whitespace-only"),
+            ElisionReason::Replacement => write!(f, "replacement"),
+            ElisionReason::AttributeAddition => write!(f, "attribute-only"),
+            ElisionReason::ScopeWrapper => write!(f, "scope-wrapper"),
+            ElisionReason::Similar => write!(f, "similar"),
+            ElisionReason::NetNoop => write!(f, "net-no-op"),
+            ElisionReason::Suppression => write!(f, "suppression-only"),
+            ElisionReason::Identical => write!(f, "identical"),
+            ElisionReason::LowChangeRatio => write!(f, "low-change-ratio"),
+        }
+    }
+}
+
+impl ElisionReason {
+    /// Every reason, in declaration order.
+    pub const ALL: &'static [ElisionReason] = &[
+        ElisionReason::Whitespace,
+        ElisionReason::Replacement,
+        ElisionReason::AttributeAddition,
+        ElisionReason::ScopeWrapper,
+        ElisionReason::Similar,
+        ElisionReason::NetNoop,
+        ElisionReason::Suppression,
+        ElisionReason::Identical,
+        ElisionReason::LowChangeRatio,
+    ];
+}
+
+/// Parses the labels produced by `Display`, e.g. `whitespace-only`.
+impl FromStr for ElisionReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ElisionReason::ALL
+            .iter()
+            .find(|reason| reason.to_string() == s)
+            .cloned()
+            .ok_or_else(|| {
+                let reasons = ElisionReason::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown elision reason {s:?}; expected one of {}",
+                    reasons.join(", ")
+                )
+            })
+    }
+}
+
+/// The outcome of evaluating a single changed block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Keep,
+    Elide(ElisionReason),
+}
+
+/// Decides whether a changed block consisting of `removed` and `added` lines should be elided.
+///
+/// This is the core of `process_file_diffs`, exposed so the heuristics can be exercised without
+/// constructing a full diff.
+pub fn decide(removed: &[&str], added: &[&str], config: &ProcessConfig) -> Decision {
+    evaluate(removed, added, config).0
+}
+
+/// Implements `decide`, also returning the replacements that were applied when the block is
+/// elided as a replacement.
+fn evaluate<'c>(
+    removed: &[&str],
+    added: &[&str],
+    config: &'c ProcessConfig,
+) -> (Decision, Vec<&'c Replacement>) {
+    // TODO: For now, hardcode the checks.
+    if removed.is_empty() || added.is_empty() {
+        return (Decision::Keep, Vec::new());
+    }
+    if removed
+        .iter()
+        .chain(added)
+        .any(|line| line.len() > config.max_line_length)
+    {
+        return (Decision::Keep, Vec::new());
+    }
+    let (removed_text, added_text) = profile::time_normalization(|| {
+        (config.pipeline.apply(removed), config.pipeline.apply(added))
+    });
+    if removed_text == added_text {
+        return (Decision::Elide(ElisionReason::Whitespace), Vec::new());
+    }
+    // Attempt to transform the before (aka removed) to the after (aka
+    // added). Is this efficient? Not particularly. Does it work? Ish.
+    let (transformed_text, applied) = profile::time_normalization(|| {
+        if let Some(matcher) = &config.replacement_matcher {
+            return matcher.apply(removed_text, &config.replacements);
+        }
+        let mut applied = Vec::new();
+        let text = config
+            .replacements
+            .iter()
+            .fold(removed_text, |current, replacement| {
+                if !replacement.is_match(&current) {
+                    return current;
+                }
+                applied.push(replacement);
+                replacement.apply(&current)
+            });
+        (text, applied)
+    });
+    if transformed_text == added_text {
+        return (Decision::Elide(ElisionReason::Replacement), applied);
+    }
+    if config.similarity_metric == SimilarityMetric::LineLevenshtein {
+        let changed = Changed {
+            removed: removed.to_vec(),
+            added: added.to_vec(),
+        };
+        let longest = removed.len().max(added.len());
+        let similarity = 1.0 - changed.levenshtein_distance() as f64 / longest as f64;
+        if similarity >= config.similarity_threshold {
+            return (Decision::Elide(ElisionReason::Similar), Vec::new());
+        }
+    } else if config.similarity_metric != SimilarityMetric::Exact {
+        let transformed_tokens = transformed_text.split_whitespace().collect::<Vec<_>>();
+        let added_tokens = added_text.split_whitespace().collect::<Vec<_>>();
+        let similarity = config
+            .similarity_metric
+            .similarity(&transformed_tokens, &added_tokens);
+        if similarity >= config.similarity_threshold {
+            return (Decision::Elide(ElisionReason::Similar), Vec::new());
+        }
+    }
+    (Decision::Keep, Vec::new())
+}
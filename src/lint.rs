@@ -0,0 +1,98 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Replacement;
+use std::fmt;
+
+/// A problem found by linting a `ProcessConfig`.
+#[derive(Clone, Debug)]
+pub enum LintWarning {
+    OverlappingReplacement(OverlappingReplacementWarning),
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintWarning::OverlappingReplacement(warning) => warning.fmt(f),
+        }
+    }
+}
+
+/// How two replacements interfere with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapKind {
+    /// The `after` of the first replacement contains the `before` of the second, so the second
+    /// rewrites what the first produced.
+    Cascade,
+    /// The `before` of the second replacement contains the `before` of the first, so the first
+    /// rewrites every match of the second before it is applied.
+    Shadowing,
+}
+
+/// Two replacements that could both apply to the same text. Replacements are applied in order,
+/// so `first` is the one that comes first.
+#[derive(Clone, Debug)]
+pub struct OverlappingReplacementWarning {
+    pub kind: OverlapKind,
+    pub first: Replacement,
+    pub second: Replacement,
+}
+
+impl fmt::Display for OverlappingReplacementWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, second) = (&self.first, &self.second);
+        match self.kind {
+            OverlapKind::Cascade => write!(
+                f,
+                "replacement `{first}` produces {:?}, which replacement `{second}` rewrites again",
+                second.before
+            ),
+            OverlapKind::Shadowing => write!(
+                f,
+                "replacement `{first}` rewrites part of {:?} before replacement `{second}` can \
+                 match it",
+                second.before
+            ),
+        }
+    }
+}
+
+/// Checks every pair of `replacements`, in the order they are applied, for overlaps.
+pub fn lint_replacements(replacements: &[Replacement]) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    for (i, first) in replacements.iter().enumerate() {
+        for second in &replacements[i + 1..] {
+            let mut warn = |kind| {
+                warnings.push(LintWarning::OverlappingReplacement(
+                    OverlappingReplacementWarning {
+                        kind,
+                        first: first.clone(),
+                        second: second.clone(),
+                    },
+                ))
+            };
+            // Whether patterns overlap cannot be told from their text.
+            if first.pattern.is_some() || second.pattern.is_some() {
+                continue;
+            }
+            if first.after.contains(&*second.before) {
+                warn(OverlapKind::Cascade);
+            }
+            if second.before.contains(&*first.before) {
+                warn(OverlapKind::Shadowing);
+            }
+        }
+    }
+    warnings
+}
@@ -12,295 +12,925 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::fmt;
-use std::io::{self, Read};
-
-#[derive(Debug)]
-struct FileDiff<'a> {
-    header: &'a str,
-    chunks: Vec<Chunk<'a>>,
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use diffreducer::diff_summary::Report;
+use diffreducer::heuristics::{CaseFolder, HeuristicPipeline};
+use diffreducer::similarity::SimilarityMetric;
+use diffreducer::test_runner::{self, FixtureOutcome};
+use diffreducer::{
+    canonicalize, derive_replacements, find_parse_errors, parse_file_diffs,
+    process_file_diffs_with_stats, profile, rules_from_toml, ChunkBlock, DiffParser, ElisionReason,
+    FileFilter, FilterErrorPolicy, Markers, ParseErrorPolicy, ParserConfig, PatchSet,
+    ProcessConfig, Replacement, ReplacementRegistry, Stats,
+};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+
+/// Filters out "purely mechanical" changes from a unified diff read from stdin.
+#[derive(Parser)]
+struct Args {
+    /// Reduce each FILE in turn instead of reading stdin, writing the results to stdout in the
+    /// same order.
+    #[arg(
+        value_name = "FILE",
+        conflicts_with_all = [
+            "recursive",
+            "test_mode",
+            "check_parseable",
+            "delimiter",
+            "read_null_separated",
+        ]
+    )]
+    files: Vec<PathBuf>,
+
+    /// Instead of reading stdin, reduce every `.patch` and `.diff` file under DIR, writing each
+    /// result next to its input as `NAME.reduced.patch` (or `.diff`), which is overwritten if it
+    /// exists and skipped as an input. A file that fails to reduce is reported and skipped.
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["delimiter", "split_output_dir"])]
+    recursive: Option<PathBuf>,
+
+    /// Instead of reading stdin, reduce every `NAME.input.diff` in DIR and compare the result with
+    /// `NAME.expected.diff`, printing whether each fixture passed. Fails if any did not.
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["recursive", "delimiter", "read_null_separated"]
+    )]
+    test_mode: Option<PathBuf>,
+
+    /// Only check that stdin is a well-formed unified diff, printing each problem found with its
+    /// line number to stderr, and fail if there were any. Nothing is written to stdout.
+    #[arg(
+        long,
+        conflicts_with_all = ["recursive", "test_mode", "delimiter", "read_null_separated"]
+    )]
+    check_parseable: bool,
+
+    /// With --recursive, write the results to the same relative paths under DIR instead. DIR
+    /// should not be inside the input directory, or the results are reduced again on the next run.
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "recursive",
+        conflicts_with = "in_place"
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// With --recursive, overwrite each input with its result.
+    #[arg(long, requires = "recursive")]
+    in_place: bool,
+
+    /// Re-emit surviving changes with at most N lines of context and recomputed hunk headers.
+    /// With 0, each run of changes becomes its own minimal hunk.
+    #[arg(long, value_name = "N")]
+    context_lines: Option<usize>,
+
+    /// Make sure the reduced diff still applies to the original files: surviving changes are
+    /// re-emitted with recomputed hunk headers and 3 lines of context, unless --context-lines says
+    /// otherwise, and `\ No newline at end of file` markers are kept.
+    #[arg(
+        long,
+        conflicts_with_all = ["include_unchanged_files", "keep_first", "group_identical_hunks"]
+    )]
+    applicable: bool,
+
+    /// Instead of reducing the diff, re-emit it in a canonical form: files sorted by path, no
+    /// `index` lines, LF line endings and at most three lines of context.
+    #[arg(long)]
+    canonicalize: bool,
+
+    /// Order each file's hunks by their start line before reducing, for tools that emit them out
+    /// of order. Overlapping hunks are reported as a warning.
+    #[arg(long)]
+    sort_hunks: bool,
+
+    /// Report how long each phase took to stderr.
+    #[arg(long)]
+    profile: bool,
+
+    /// Print how each changed block is transformed by the normalization steps to stderr.
+    #[arg(long)]
+    trace_heuristics: bool,
+
+    /// Read settings from the TOML file FILE. Currently, only a `[file_filter]` table with
+    /// `include` and `exclude` lists of globs, e.g. `exclude = ["third_party/**"]`, is supported;
+    /// files whose path does not pass it are dropped.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// The checkout the diff is meant to apply to, for --require-context-match.
+    #[arg(long, value_name = "DIR")]
+    apply_to: Option<PathBuf>,
+
+    /// Check each file's context lines against the same file under --apply-to before reducing
+    /// it. Files that do not match are reported as a warning and passed through unchanged.
+    #[arg(long, requires = "apply_to")]
+    require_context_match: bool,
+
+    /// Elide blocks that only add `[[deprecated]]`, `@Deprecated` or `#[deprecated]` attributes
+    /// right before an unchanged line.
+    #[arg(long)]
+    elide_deprecated_attributes: bool,
+
+    /// Elide blocks that only add lines matching REGEX right before an unchanged line. May be
+    /// repeated.
+    #[arg(long, value_name = "REGEX")]
+    attribute_pattern: Vec<String>,
+
+    /// Elide blocks that only add statements silencing unused variable warnings: `(void)x;` or
+    /// `std::ignore = x;` in C++, and `let _ = x;` or `_ = x;` in Rust. A block is only elided
+    /// if those are all it adds.
+    #[arg(long)]
+    elide_unused_suppressions: bool,
+
+    /// Elide blocks that only add lines matching REGEX, e.g. other warning suppressions. May be
+    /// repeated.
+    #[arg(long, value_name = "REGEX")]
+    suppression_pattern: Vec<String>,
+
+    /// Elide blocks that only add `namespace foo {` or `extern "C" {` and the matching closing
+    /// brace around existing code. The braces must balance within each file's diff.
+    #[arg(long)]
+    elide_scope_wrappers: bool,
+
+    /// Keep changes whose removed and added lines are byte-for-byte identical, which are elided
+    /// before anything else by default.
+    #[arg(long)]
+    keep_identical_changes: bool,
+
+    /// Search for all replacements at once, which is faster with many replacement rules, e.g.
+    /// from --rules-from-commit. The result is the same.
+    #[arg(long)]
+    parallel_replacements: bool,
+
+    /// Show a deleted file and an added file whose lines are at least F similar, from 0 to 1, as
+    /// a rename, like `git diff --find-renames`, so only the differences between them are left to
+    /// reduce.
+    #[arg(long, value_name = "F")]
+    find_renames: Option<f64>,
+
+    /// Elide pairs of changes in the same file that cancel out, e.g. a change that is reverted
+    /// in a later hunk. Both sides are compared after normalization.
+    #[arg(long)]
+    elide_net_noop: bool,
+
+    /// Elide the changes in any hunk where changed lines make up less than R of its lines, from
+    /// 0 to 1, counting only changes that are left after the other reductions.
+    #[arg(long, value_name = "R")]
+    min_ratio: Option<f64>,
+
+    /// Compare TOKEN case-insensitively, e.g. so that a change from `TRUE` to `true` is elided.
+    /// Only whole words are matched, and everything else stays case-sensitive. May be repeated.
+    #[arg(long, value_name = "TOKEN")]
+    ignore_case_for: Vec<String>,
+
+    /// Apply the replacements of the built-in set NAME.
+    #[arg(long, value_name = "NAME", default_value = ReplacementRegistry::DEFAULT_SET)]
+    replacement_set: String,
+
+    /// Apply the replacements listed in FILE instead of a built-in set. Each `[[rules]]` entry
+    /// has a `before` and an `after` string.
+    #[arg(long, value_name = "FILE", conflicts_with = "replacement_set")]
+    rules: Option<PathBuf>,
+
+    /// Also apply replacements derived from COMMIT in the current git repository, e.g. the
+    /// commit that performed a migration, to strip the same migration from this diff. Only
+    /// identifier-level substitutions that COMMIT made consistently and repeatedly are used.
+    #[arg(long, value_name = "COMMIT")]
+    rules_from_commit: Option<String>,
+
+    /// How many times --rules-from-commit must see a substitution before using it.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        requires = "rules_from_commit"
+    )]
+    rules_min_occurrences: usize,
+
+    /// Treat configuration warnings, e.g. a replacement that changes nothing, as errors.
+    #[arg(long)]
+    strict_config: bool,
+
+    /// Fail if a parsed file has chunks that are out of order, overlap, or disagree with their
+    /// headers about how many lines they have.
+    #[arg(long)]
+    strict: bool,
+
+    /// Only output files where at least one changed block was elided, to review what the
+    /// reduction actually touched.
+    #[arg(long)]
+    only_reduced_files: bool,
+
+    /// Drop files where every change was elided. This is the default.
+    #[arg(long, overrides_with = "include_unchanged_files")]
+    omit_unchanged_files: bool,
+
+    /// Keep files where every change was elided, as just their header, to show that they were
+    /// part of the diff. The output no longer applies.
+    #[arg(long, overrides_with = "omit_unchanged_files")]
+    include_unchanged_files: bool,
+
+    /// Drop files with fewer than N removed and added lines left after reduction.
+    #[arg(long, value_name = "N")]
+    min_file_changes: Option<usize>,
+
+    /// Pass files with more than N removed and added lines through unchanged. Set RUST_LOG=debug
+    /// to see which files are affected.
+    #[arg(long, value_name = "N")]
+    max_file_changes: Option<usize>,
+
+    /// Also elide blocks whose sides are similar but not identical after normalization, scored
+    /// by METRIC: exact, levenshtein, line-levenshtein, jaccard or lcs.
+    #[arg(long, value_name = "METRIC", default_value_t = SimilarityMetric::Exact)]
+    similarity_metric: SimilarityMetric,
+
+    /// The similarity from 0 to 1 at which --similarity-metric elides a block.
+    #[arg(long, value_name = "F", default_value_t = ProcessConfig::default().similarity_threshold)]
+    similarity_threshold: f64,
+
+    /// What to do with malformed input, e.g. a hunk with fewer lines than its header says: fail,
+    /// warn and skip the hunk, or ignore it and parse the hunk as well as possible.
+    #[arg(long, value_name = "POLICY", default_value_t = ParseErrorPolicy::Ignore)]
+    on_parse_error: ParseErrorPolicy,
+
+    /// What to do with an --attribute-pattern or --suppression-pattern that does not compile or
+    /// is too large: fail; warn and keep the blocks it would have been checked against; or
+    /// ignore it and elide those blocks.
+    #[arg(long, value_name = "POLICY", default_value_t = FilterErrorPolicy::Fail)]
+    on_filter_error: FilterErrorPolicy,
+
+    /// Leave a `[diffreducer: elided]` note where each change was elided in a surviving hunk. The
+    /// notes are context lines, so the output no longer applies.
+    #[arg(long, conflicts_with = "context_lines")]
+    annotate: bool,
+
+    /// Like --annotate, but also say why each change was elided, e.g.
+    /// `[diffreducer: elided (whitespace-only)]`.
+    #[arg(long, conflicts_with = "context_lines")]
+    elide_reason_column: bool,
+
+    /// Only output the first N surviving hunks of each file, followed by a note saying how many
+    /// were left out. The note is a context line, so the output no longer applies.
+    #[arg(long, value_name = "N")]
+    keep_first: Option<NonZeroUsize>,
+
+    /// Find blocks that only remove lines that another block, possibly in another file, only adds,
+    /// and mark them with --output-moved-removed-marker and --output-moved-added-marker to show
+    /// that the code was moved rather than deleted and added. The output no longer applies
+    /// unless those are set to `-` and `+`.
+    #[arg(long)]
+    detect_moves: bool,
+
+    /// Show hunks that are identical across files only once, with a note listing the other
+    /// files. The notes are context lines, so the output no longer applies.
+    #[arg(long)]
+    group_identical_hunks: bool,
+
+    /// Emit each file header once, followed by all of its surviving hunks. This is the default.
+    #[arg(long, overrides_with = "no_group")]
+    group_by_file: bool,
+
+    /// Emit each surviving hunk as a standalone patch, repeating its file header.
+    #[arg(long, overrides_with = "group_by_file")]
+    no_group: bool,
+
+    /// Instead of writing to stdout, write each surviving hunk as a standalone patch to its own
+    /// file in DIR, named after its position and file. With --delimiter, the files are rewritten
+    /// for each diff.
+    #[arg(long, value_name = "DIR")]
+    split_output_dir: Option<PathBuf>,
+
+    /// Write the result as a unified diff, as a JSON document, or as one JSON object per line and
+    /// file.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Diff)]
+    output_format: OutputFormat,
+
+    /// In JSON output, give each changed block a `fingerprint`: a stable hash of its content after
+    /// normalization, so identical changes can be grouped downstream.
+    #[arg(long)]
+    fingerprint: bool,
+
+    /// Prefix context lines with C instead of a space. The output will not be a valid diff.
+    #[arg(long, value_name = "C", default_value_t = Markers::UNIFIED.context)]
+    output_context_marker: char,
+
+    /// Prefix added lines with C instead of `+`.
+    #[arg(long, value_name = "C", default_value_t = Markers::UNIFIED.added)]
+    output_added_marker: char,
+
+    /// Prefix removed lines with C instead of `-`.
+    #[arg(long, value_name = "C", default_value_t = Markers::UNIFIED.removed)]
+    output_removed_marker: char,
+
+    /// With --detect-moves, prefix removed lines that were moved elsewhere with C.
+    #[arg(long, value_name = "C", default_value_t = '<')]
+    output_moved_removed_marker: char,
+
+    /// With --detect-moves, prefix added lines that were moved from elsewhere with C.
+    #[arg(long, value_name = "C", default_value_t = '>')]
+    output_moved_added_marker: char,
+
+    /// Warn if a single rule is responsible for more than PCT percent of all elided blocks, as
+    /// it may be broad enough to hide real changes.
+    #[arg(long, value_name = "PCT")]
+    rule_stats_threshold: Option<f64>,
+
+    /// Describe each input file on stderr: how many blocks were elided, whether it was kept and
+    /// which replacements were found in it.
+    #[arg(long, short)]
+    verbose: bool,
+
+    /// Report to stderr how many changed blocks were elided, and how many files and chunks are
+    /// left of the input.
+    #[arg(long)]
+    stats: bool,
+
+    /// Write a detailed report with a per-file and per-rule breakdown to FILE. With
+    /// --delimiter or --recursive, it is rewritten for each diff.
+    #[arg(long, value_name = "FILE")]
+    report_file: Option<PathBuf>,
+
+    /// Write the --report-file as text, or as JSON with a summary of each file in the output.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// Exit with status 2 if any block was elided for REASON: whitespace-only, replacement,
+    /// attribute-only, scope-wrapper, similar, net-no-op, suppression-only, identical or
+    /// low-change-ratio. May be repeated.
+    #[arg(long, value_name = "REASON")]
+    fail_on_reason: Vec<ElisionReason>,
+
+    /// Keep reading after each line equal to STR, reducing everything since the previous one
+    /// and writing the result followed by STR. This allows a long-running process to reduce a
+    /// stream of diffs, e.g. from a pipe or socket.
+    #[arg(long, value_name = "STR", allow_hyphen_values = true)]
+    delimiter: Option<String>,
+
+    /// Read all of stdin and split it on NUL bytes, reducing each part as a separate diff. The
+    /// results are separated by NUL bytes as well, so they can be told apart.
+    #[arg(long, conflicts_with_all = ["delimiter", "recursive"])]
+    read_null_separated: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Diff,
+    Json,
+    Ndjson,
 }
 
-impl<'a> fmt::Display for FileDiff<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.header)?;
-        for chunk in &self.chunks {
-            write!(f, "{chunk}")?;
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl Args {
+    fn markers(&self) -> Markers {
+        Markers {
+            context: self.output_context_marker,
+            added: self.output_added_marker,
+            removed: self.output_removed_marker,
+            moved_removed: self.output_moved_removed_marker,
+            moved_added: self.output_moved_added_marker,
         }
-        Ok(())
     }
 }
 
-#[derive(Debug)]
-struct Chunk<'a> {
-    header: &'a str,
-    blocks: Vec<ChunkBlock<'a>>,
-}
+/// The context `--applicable` emits by default, like `diff -u`.
+const DEFAULT_APPLICABLE_CONTEXT_LINES: usize = 3;
 
-impl<'a> fmt::Display for Chunk<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.header)?;
-        for block in &self.blocks {
-            write!(f, "{block}")?;
+fn main() -> Result<()> {
+    // Show warnings, e.g. from --on-parse-error=warn, unless RUST_LOG says otherwise.
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let args = Args::parse();
+    let mut attribute_patterns = Vec::new();
+    if args.elide_deprecated_attributes {
+        attribute_patterns.extend(ProcessConfig::deprecated_attribute_patterns());
+    }
+    let mut suppression_patterns = Vec::new();
+    if args.elide_unused_suppressions {
+        suppression_patterns.extend(ProcessConfig::unused_variable_suppression_patterns());
+    }
+    let mut builder = ProcessConfig::builder().replacement_set(&args.replacement_set);
+    for pattern in &args.attribute_pattern {
+        builder = builder.attribute_pattern(pattern);
+    }
+    for pattern in &args.suppression_pattern {
+        builder = builder.suppression_pattern(pattern);
+    }
+    if let Some(path) = &args.config {
+        let config = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let file_filter = FileFilter::from_toml(&config)
+            .with_context(|| format!("failed to load {}", path.display()))?;
+        builder = builder.file_filter(file_filter);
+    }
+    if !args.ignore_case_for.is_empty() {
+        let mut pipeline = HeuristicPipeline::default();
+        pipeline.push(Box::new(CaseFolder::new(&args.ignore_case_for)));
+        builder = builder.pipeline(pipeline);
+    }
+    if let Some(path) = &args.rules {
+        let rules = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let rules = rules_from_toml(&rules)
+            .with_context(|| format!("failed to load {}", path.display()))?;
+        builder = builder.replacements(rules);
+    }
+    if let Some(commit) = &args.rules_from_commit {
+        for replacement in rules_from_commit(commit, args.rules_min_occurrences)? {
+            if args.verbose {
+                eprintln!(
+                    "{commit}: derived {:?} -> {:?}",
+                    replacement.before, replacement.after
+                );
+            }
+            builder = builder.replacement(replacement);
+        }
+    }
+    let config = builder
+        .context_lines(
+            args.context_lines
+                .or(args.applicable.then_some(DEFAULT_APPLICABLE_CONTEXT_LINES)),
+        )
+        .reference_dir(args.apply_to.clone())
+        .require_context_match(args.require_context_match)
+        .attribute_patterns(attribute_patterns)
+        .suppression_patterns(suppression_patterns)
+        .only_reduced_files(args.only_reduced_files)
+        .include_unchanged_files(args.include_unchanged_files)
+        .keep_first(args.keep_first)
+        .elide_scope_wrappers(args.elide_scope_wrappers)
+        .parallel_replacements(args.parallel_replacements)
+        .rename_detection_threshold(args.find_renames)
+        .elide_identical_removed_added(!args.keep_identical_changes)
+        .elide_net_noops(args.elide_net_noop)
+        .min_change_ratio(args.min_ratio)
+        .annotate_elisions(args.annotate || args.elide_reason_column)
+        .annotate_elision_reasons(args.elide_reason_column)
+        .min_file_changes(args.min_file_changes)
+        .max_file_changes(args.max_file_changes)
+        .similarity_metric(args.similarity_metric)
+        .similarity_threshold(args.similarity_threshold)
+        .on_parse_error(args.on_parse_error)
+        .on_filter_error(args.on_filter_error)
+        .build()?;
+    args.markers().validate()?;
+    if args.verbose {
+        if let Some(name) = &config.replacement_set {
+            // `build` already checked that the set exists.
+            let version = &ReplacementRegistry::default().rules[name].version;
+            eprintln!("replacement set: {name} (version {version})");
         }
-        Ok(())
     }
+    let warnings = config
+        .validate()
+        .iter()
+        .map(ToString::to_string)
+        .chain(config.lint_warnings.iter().map(ToString::to_string))
+        .collect::<Vec<_>>();
+    if args.strict_config && !warnings.is_empty() {
+        bail!(
+            "invalid configuration:\n{}",
+            warnings
+                .iter()
+                .map(|warning| format!("  {warning}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    if let Some(dir) = &args.test_mode {
+        return run_fixtures(dir, &config);
+    }
+    if args.check_parseable {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        let errors = find_parse_errors(&input);
+        for error in &errors {
+            eprintln!("error: {error}");
+        }
+        if !errors.is_empty() {
+            bail!("found {} problem(s) in the diff", errors.len());
+        }
+        return Ok(());
+    }
+
+    let elisions_by_reason = run(&args, &config)?;
+    let failures = args
+        .fail_on_reason
+        .iter()
+        .filter_map(|reason| Some((reason, elisions_by_reason.get(reason)?)))
+        .collect::<Vec<_>>();
+    for (reason, count) in &failures {
+        eprintln!("error: {count} block(s) elided as {reason}");
+    }
+    if !failures.is_empty() {
+        io::stdout().flush()?;
+        process::exit(2);
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-enum ChunkBlock<'a> {
-    Context(Vec<&'a str>),
-    Changed(Changed<'a>),
+/// Runs the fixtures in `dir`, printing a line for each.
+fn run_fixtures(dir: &Path, config: &ProcessConfig) -> Result<()> {
+    let results = test_runner::run_fixtures(dir, config)
+        .with_context(|| format!("failed to run the fixtures in {}", dir.display()))?;
+    for result in &results {
+        match &result.outcome {
+            FixtureOutcome::Pass => println!("PASS {}", result.name),
+            FixtureOutcome::Fail { expected, actual } => {
+                let line = expected
+                    .lines()
+                    .zip(actual.lines())
+                    .take_while(|(expected, actual)| expected == actual)
+                    .count()
+                    + 1;
+                println!(
+                    "FAIL {}: output differs from {} at line {line}",
+                    result.name,
+                    test_runner::expected_path(dir, &result.name).display()
+                );
+            }
+            FixtureOutcome::MissingExpected => println!(
+                "FAIL {}: {} is missing",
+                result.name,
+                test_runner::expected_path(dir, &result.name).display()
+            ),
+        }
+    }
+    let failures = results.iter().filter(|result| !result.passed()).count();
+    if failures > 0 {
+        bail!("{failures} of {} fixtures failed", results.len());
+    }
+    Ok(())
 }
 
-impl<'a> fmt::Display for ChunkBlock<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ChunkBlock::Context(lines) => {
-                for line in lines {
-                    writeln!(f, " {line}")?;
+/// Reduces everything read from stdin or the files named on the command line, returning how many
+/// blocks were elided for each reason.
+fn run(args: &Args, config: &ProcessConfig) -> Result<BTreeMap<ElisionReason, usize>> {
+    let mut elisions_by_reason = BTreeMap::new();
+    let mut record = |stats: Stats| {
+        for (reason, count) in stats.elisions_by_reason {
+            *elisions_by_reason.entry(reason).or_default() += count;
+        }
+    };
+
+    if let Some(dir) = &args.recursive {
+        let mut patches = Vec::new();
+        find_patches(dir, &mut patches)
+            .with_context(|| format!("failed to walk {}", dir.display()))?;
+        let mut failures = 0;
+        for patch in &patches {
+            match reduce_file(patch, &output_path(args, dir, patch), args, config) {
+                Ok(stats) => record(stats),
+                Err(error) => {
+                    eprintln!("error: {}: {error:#}", patch.display());
+                    failures += 1;
                 }
             }
-            ChunkBlock::Changed(changed) => {
-                write!(f, "{changed}")?;
+        }
+        if failures > 0 {
+            bail!("failed to reduce {failures} of {} files", patches.len());
+        }
+        return Ok(elisions_by_reason);
+    }
+
+    if !args.files.is_empty() {
+        let mut stdout = io::stdout().lock();
+        for path in &args.files {
+            let input = fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            record(
+                reduce(&input, args, config, &mut stdout)
+                    .with_context(|| format!("failed to reduce {}", path.display()))?,
+            );
+        }
+        return Ok(elisions_by_reason);
+    }
+
+    if args.read_null_separated {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        let mut stdout = io::stdout().lock();
+        // A trailing NUL, e.g. from `printf '%s\0'`, does not start another diff.
+        let diffs = input.strip_suffix('\0').unwrap_or(&input).split('\0');
+        for (i, diff) in diffs.enumerate() {
+            if i > 0 {
+                stdout.write_all(b"\0")?;
             }
-        };
-        Ok(())
+            record(reduce(diff, args, config, &mut stdout)?);
+        }
+        return Ok(elisions_by_reason);
     }
-}
 
-#[derive(Debug)]
-struct Changed<'a> {
-    removed: Vec<&'a str>,
-    added: Vec<&'a str>,
-}
+    let Some(delimiter) = &args.delimiter else {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        record(reduce(&input, args, config, &mut io::stdout())?);
+        return Ok(elisions_by_reason);
+    };
 
-impl<'a> fmt::Display for Changed<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for line in &self.removed {
-            writeln!(f, "-{line}")?;
+    // Reduce each delimited segment as soon as it is complete, reusing the buffers between them.
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut input = String::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let eof = stdin.read_line(&mut line)? == 0;
+        if !eof && line.trim_end_matches(['\r', '\n']) != delimiter {
+            input.push_str(&line);
+            continue;
+        }
+        if eof && input.is_empty() {
+            break;
         }
-        for line in &self.added {
-            writeln!(f, "+{line}")?;
+        record(reduce(&input, args, config, &mut stdout)?);
+        input.clear();
+        if eof {
+            break;
         }
-        Ok(())
+        writeln!(stdout, "{delimiter}")?;
+        stdout.flush()?;
     }
+    Ok(elisions_by_reason)
 }
 
-// TODO: Think of an actual abstraction :)
-struct Replacement {
-    before: &'static str,
-    after: &'static str,
+/// Collects the `.patch` and `.diff` files under `dir`, in a stable order. Symlinked directories are
+/// not followed, and earlier results (`*.reduced.patch`) are skipped.
+fn find_patches(dir: &Path, patches: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            find_patches(&path, patches)?;
+            continue;
+        }
+        let is_patch = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("patch" | "diff")
+        );
+        let is_result = path
+            .file_stem()
+            .is_some_and(|stem| Path::new(stem).extension() == Some("reduced".as_ref()));
+        if is_patch && !is_result {
+            patches.push(path);
+        }
+    }
+    Ok(())
 }
 
-const REPLACEMENTS: &[Replacement] = &[Replacement {
-    before: "NOTREACHED_NORETURN",
-    after: "NOTREACHED",
-}];
-
-fn parse_file_diffs(input: &str) -> Vec<FileDiff> {
-    // diff --git a/ash/accelerators/accelerator_capslock_state_machine.cc b/ash/accelerators/accelerator_capslock_state_machine.cc
-    // index 28c373b242560..75f0f75e738a2 100644
-    // --- a/ash/accelerators/accelerator_capslock_state_machine.cc
-    // +++ b/ash/accelerators/accelerator_capslock_state_machine.cc
-    static FILE_HEADER_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(concat!(
-            r"(?m)",
-            r"^(?:diff --git a/.+ b/.+\nindex [0-9a-f]+..[0-9a-f]+ \d+\n)?",
-            r"--- .+\n",
-            r"[+]{3} .+\n",
-        ))
-        .unwrap()
-    });
-    // @@ -27,8 +27,8 @@ AcceleratorCapslockStateMachine::AcceleratorCapslockStateMachine(
-    static CHUNK_HEADER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)@@ .+\n").unwrap());
-
-    let file_headers = FILE_HEADER_RE
-        .find_iter(input)
-        .map(Some)
-        .chain(Some(None))
-        .collect::<Vec<_>>();
+/// Returns where --recursive writes the result for `patch`, found under `dir`.
+fn output_path(args: &Args, dir: &Path, patch: &Path) -> PathBuf {
+    if args.in_place {
+        return patch.to_owned();
+    }
+    if let Some(output_dir) = &args.output_dir {
+        return output_dir.join(patch.strip_prefix(dir).unwrap_or(patch));
+    }
+    let extension = patch.extension().unwrap_or_default().to_string_lossy();
+    patch.with_extension(format!("reduced.{extension}"))
+}
 
-    file_headers
-        .iter()
-        .zip(file_headers.iter().skip(1))
-        .map(|(current, next)| {
-            // By construction, there should always be a `current`.
-            let current = current.unwrap();
-            let header = current.as_str();
-
-            let file_diff_text = match next {
-                Some(next) => &input[current.end()..next.start()],
-                None => &input[current.start()..],
-            };
-
-            let chunk_headers = CHUNK_HEADER_RE
-                .find_iter(file_diff_text)
-                .map(Some)
-                .chain(Some(None))
-                .collect::<Vec<_>>();
+/// Derives replacements from the diff `git show` prints for `commit`.
+fn rules_from_commit(commit: &str, min_occurrences: usize) -> Result<Vec<Replacement>> {
+    let output = process::Command::new("git")
+        .args([
+            "show",
+            "--format=",
+            "--no-color",
+            "--no-ext-diff",
+            commit,
+            "--",
+        ])
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git show {commit} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let diff = String::from_utf8_lossy(&output.stdout);
+    Ok(derive_replacements(
+        &parse_file_diffs(&diff),
+        min_occurrences,
+    ))
+}
 
-            let chunks = chunk_headers
-                .iter()
-                .zip(chunk_headers.iter().skip(1))
-                .map(|(current, next)| {
-                    // By construction, there should always be a `current`.
-                    let current = current.unwrap();
-                    let header = current.as_str();
-
-                    let chunk_text = match next {
-                        Some(next) => &file_diff_text[current.end()..next.start()],
-                        None => &file_diff_text[current.end()..],
-                    };
+/// Reduces the diff in `input`, writing the result to `output`.
+fn reduce_file(input: &Path, output: &Path, args: &Args, config: &ProcessConfig) -> Result<Stats> {
+    let text = fs::read_to_string(input).context("failed to read")?;
+    let mut reduced = Vec::new();
+    let stats = reduce(&text, args, config, &mut reduced)?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(output, reduced).with_context(|| format!("failed to write {}", output.display()))?;
+    Ok(stats)
+}
 
-                    let chunk_text_lines = chunk_text
-                        .lines()
-                        .map(|line| line.split_at(1))
-                        .collect::<Vec<_>>();
-                    let blocks = chunk_text_lines
-                        .chunk_by(|&(a, _), &(b, _)| a == b || a == "-" && b == "+")
-                        .map(|lines| {
-                            let (removed, added) = lines.iter().fold(
-                                (Vec::new(), Vec::new()),
-                                |(mut removed, mut added), &(prefix, line)| {
-                                    match prefix {
-                                        " " => (),
-                                        "-" => removed.push(line),
-                                        "+" => added.push(line),
-                                        "\\" => assert_eq!(line, " No newline at end of file"),
-                                        _ => panic!("unexpected prefix {prefix} at {line}!"),
-                                    };
-                                    (removed, added)
-                                },
-                            );
-                            if removed.is_empty() && added.is_empty() {
-                                ChunkBlock::Context(
-                                    lines.iter().map(|(_prefix, line)| line).copied().collect(),
-                                )
-                            } else {
-                                ChunkBlock::Changed(Changed { removed, added })
-                            }
-                        })
-                        .collect::<Vec<_>>();
-
-                    Chunk { header, blocks }
-                })
-                .collect::<Vec<_>>();
+/// Reduces (or canonicalizes) one complete diff, writing the result to `output` and any requested
+/// reports to stderr.
+fn reduce(
+    input: &str,
+    args: &Args,
+    config: &ProcessConfig,
+    output: &mut impl Write,
+) -> Result<Stats> {
+    let input = if args.canonicalize {
+        Cow::Owned(input.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(input)
+    };
 
-            FileDiff { header, chunks }
-        })
-        .collect()
-}
+    let normalization_start = profile::normalization_time();
+    let start = Instant::now();
+    let mut file_diffs = DiffParser::with_config(ParserConfig {
+        error_policy: config.on_parse_error,
+        preserve_no_newline_markers: args.applicable,
+        ..ParserConfig::default()
+    })
+    .parse(&input)?;
+    let parse_time = start.elapsed();
+    if args.strict {
+        let errors = file_diffs
+            .iter()
+            .filter_map(|file_diff| file_diff.well_formed().err())
+            .flatten()
+            .collect::<Vec<_>>();
+        for error in &errors {
+            eprintln!("error: {error}");
+        }
+        if !errors.is_empty() {
+            bail!("found {} malformed chunk(s)", errors.len());
+        }
+    }
+    if args.sort_hunks {
+        for file_diff in &mut file_diffs {
+            if let Err(overlap) = file_diff.sort_chunks() {
+                eprintln!("warning: {overlap}");
+            }
+        }
+    }
+    let file_count = file_diffs.len();
 
-fn process_file_diffs(file_diffs: Vec<FileDiff>) -> Vec<FileDiff> {
-    file_diffs
-        .into_iter()
-        .filter_map(|FileDiff { header, chunks }| {
-            let chunks = chunks
-                .into_iter()
-                .filter_map(|Chunk { header, blocks }| {
-                    let new_blocks = blocks
-                        .into_iter()
-                        .filter_map(|block| match block {
-                            ChunkBlock::Changed(changed) => process_changed_block(changed),
-                            ChunkBlock::Context(_) => Some(block),
-                        })
-                        .collect::<Vec<_>>();
-                    // The filtered diff here may not actually apply to the original files. A given
-                    // chunk may have multiple changed blocks, but the filtering mechanism used
-                    // here does not restore those to "not changed" lines; it just drops them. This
-                    // means that there may be context lines that don't correspond to anything. Oh
-                    // well :)
-                    if new_blocks
-                        .iter()
-                        .any(|block| matches!(block, ChunkBlock::Changed(_)))
-                    {
-                        Some(Chunk {
-                            header,
-                            blocks: new_blocks,
-                        })
-                    } else {
-                        None
+    if args.trace_heuristics {
+        eprintln!("{:?}", config.pipeline);
+        for file_diff in &file_diffs {
+            for chunk in &file_diff.chunks {
+                for block in &chunk.blocks {
+                    let ChunkBlock::Changed(changed) = block else {
+                        continue;
+                    };
+                    if changed.removed.is_empty() || changed.added.is_empty() {
+                        continue;
                     }
-                })
-                .collect::<Vec<_>>();
-            if chunks.is_empty() {
-                None
-            } else {
-                Some(FileDiff { header, chunks })
+                    eprint!("{}: {}", file_diff.file_name(), chunk.header);
+                    eprint!(
+                        "{}",
+                        config.pipeline.debug_trace(&changed.removed.join("\n"))
+                    );
+                    eprint!("{}", config.pipeline.debug_trace(&changed.added.join("\n")));
+                }
             }
-        })
-        .collect()
-}
+        }
+    }
 
-fn process_changed_block(changed: Changed) -> Option<ChunkBlock> {
-    // TODO: For now, hardcode the checks.
-    if changed.removed.is_empty() || changed.added.is_empty() {
-        Some(ChunkBlock::Changed(changed))
+    let input_net_delta = args
+        .stats
+        .then(|| PatchSet::from(file_diffs.clone()).total_net_delta());
+    let mut stats = Stats::default();
+    let start = Instant::now();
+    let mut processed_diffs = PatchSet::from(if args.canonicalize {
+        canonicalize(file_diffs)
     } else {
-        // Simplifying heuristics:
-        // 1. Whitespace is not significant, so join the lines and squash consecutive runs of
-        //    whitespace characters into a space.
-        // 2. Since the above heuristic tends to produce `( `, e.g. when a function call is
-        //    reflowed to the following line, convert `( ` back to `(`.
-        // 3. Strip the comment delimiter from lines starting with `//` to improve fuzzy matching
-        //    when comments are reflowed across lines.
-        // TODO: Perhaps these heuristics should be configurable.
-        fn apply_heuristics(lines: &[&str]) -> String {
-            static MULTIPLE_WHITESPACE_RE: Lazy<Regex> =
-                Lazy::new(|| Regex::new(r"\s{2,}").unwrap());
-            fn trim_leading_comment(s: &str) -> &str {
-                let s = s.trim_start();
-                let s = s.strip_prefix("// ").unwrap_or(s);
-                s
+        process_file_diffs_with_stats(file_diffs, config, &mut stats)
+    });
+    for mismatch in &stats.context_mismatches {
+        eprintln!("warning: {mismatch}");
+    }
+    // Summarize the files before regrouping their chunks.
+    let report = match args.report_format {
+        ReportFormat::Text => stats.display_detailed(),
+        ReportFormat::Json => Report::new(&processed_diffs.files, &stats).to_json(),
+    };
+    if args.verbose {
+        for file in &stats.per_file {
+            eprint!(
+                "{}: {}/{} blocks elided, {}",
+                file.file_name,
+                file.elided_blocks,
+                file.changed_blocks,
+                if file.kept { "kept" } else { "dropped" }
+            );
+            if !file.matched_rules.is_empty() {
+                eprint!(", replaced {}", file.matched_rules.join(", "));
             }
-
-            MULTIPLE_WHITESPACE_RE
-                .replace_all(
-                    &lines
-                        .iter()
-                        .copied()
-                        .map(trim_leading_comment)
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                    " ",
-                )
-                .into_owned()
-                .replace("( ", "(")
-        }
-        let removed_text = apply_heuristics(&changed.removed);
-        let added_text = apply_heuristics(&changed.added);
-        // Attempt to transform the before (aka removed) to the after (aka
-        // added). Is this efficient? Not particularly. Does it work? Ish.
-        let transformed_text = REPLACEMENTS
-            .iter()
-            .fold(removed_text, |current, replacement| {
-                current.replace(replacement.before, replacement.after)
-            });
-        if transformed_text == added_text {
-            // TODO: Maybe this should return ChunkBlock::Elided or something?
-            None
-        } else {
-            Some(ChunkBlock::Changed(changed))
+            let annotations = processed_diffs
+                .files
+                .iter()
+                .filter(|file_diff| file.kept && file_diff.file_name() == file.file_name)
+                .flat_map(|file_diff| &file_diff.chunks)
+                .filter_map(|chunk| chunk.annotation_string())
+                .collect::<Vec<_>>();
+            if !annotations.is_empty() {
+                eprint!(", changes in {}", annotations.join(", "));
+            }
+            eprintln!();
         }
     }
-}
+    if args.detect_moves {
+        processed_diffs = processed_diffs.detect_moves(&config.pipeline);
+    }
+    if args.group_identical_hunks {
+        processed_diffs = processed_diffs.group_identical_hunks();
+    }
+    if args.no_group {
+        processed_diffs = processed_diffs.ungrouped();
+    }
+    let process_time = start.elapsed();
 
-fn main() -> Result<()> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-    let input = input;
+    let start = Instant::now();
+    if let Some(dir) = &args.split_output_dir {
+        write_split_output(&processed_diffs, dir)?;
+    } else {
+        let fingerprints = args.fingerprint.then_some(&config.pipeline);
+        let rendered = match args.output_format {
+            OutputFormat::Diff => processed_diffs.display_with(&args.markers()).to_string(),
+            OutputFormat::Json => processed_diffs.to_json(fingerprints),
+            OutputFormat::Ndjson => processed_diffs.to_ndjson(fingerprints),
+        };
+        output.write_all(rendered.as_bytes())?;
+    }
+    let render_time = start.elapsed();
 
-    let file_diffs = parse_file_diffs(&input);
+    if let Some(input_net_delta) = input_net_delta {
+        eprintln!("{}", stats.display_summary());
+        eprintln!("{}", stats.display_sizes());
+        eprintln!(
+            "net line delta: {input_net_delta:+} before, {:+} after",
+            processed_diffs.total_net_delta()
+        );
+    }
+    if let Some(path) = &args.report_file {
+        fs::write(path, report)
+            .with_context(|| format!("failed to write report to {}", path.display()))?;
+    }
 
-    let processed_diffs = process_file_diffs(file_diffs);
+    if let Some(threshold) = args.rule_stats_threshold {
+        for (rule, percent) in stats.dominant_rules(threshold) {
+            eprintln!(
+                "warning: {rule} caused {percent:.0}% of {} elisions; check that it is not too broad",
+                stats.elided_blocks
+            );
+        }
+    }
 
-    for file in processed_diffs {
-        println!("{file}");
+    if args.profile {
+        eprintln!("parse:           {parse_time:?}");
+        eprintln!("process:         {process_time:?} ({file_count} files)");
+        eprintln!(
+            "  normalization: {:?}",
+            profile::normalization_time() - normalization_start
+        );
+        eprintln!("render:          {render_time:?}");
     }
 
+    Ok(stats)
+}
+
+/// Writes each chunk in `patch_set` to `dir` as a standalone patch, e.g. `0001-src-foo.cc.patch`.
+fn write_split_output(patch_set: &PatchSet, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let chunks = patch_set
+        .files
+        .iter()
+        .flat_map(|file_diff| file_diff.chunks.iter().map(move |chunk| (file_diff, chunk)));
+    for (index, (file_diff, chunk)) in chunks.enumerate() {
+        let file_name = file_diff.file_name();
+        let path = dir.join(format!(
+            "{:04}-{}.patch",
+            index + 1,
+            file_name.replace('/', "-")
+        ));
+        fs::write(&path, chunk.to_standalone_patch(file_name))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
     Ok(())
 }
@@ -0,0 +1,86 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+
+/// The prefixes that mark each kind of line when rendering a diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Markers {
+    pub context: char,
+    pub added: char,
+    pub removed: char,
+    /// Marks removed lines that `PatchSet::detect_moves` found added elsewhere.
+    pub moved_removed: char,
+    /// Marks added lines that `PatchSet::detect_moves` found removed elsewhere.
+    pub moved_added: char,
+}
+
+impl Markers {
+    /// The prefixes of a unified diff: ` `, `+` and `-`. Moved lines are marked like any other
+    /// removed or added line.
+    pub const UNIFIED: Markers = Markers {
+        context: ' ',
+        added: '+',
+        removed: '-',
+        moved_removed: '-',
+        moved_added: '+',
+    };
+
+    /// Checks that the markers can be told apart from each other and from the line they prefix.
+    /// Moved lines may share the marker of their side, but not that of the other sides.
+    pub fn validate(&self) -> Result<(), InvalidMarkers> {
+        let markers = [
+            self.context,
+            self.added,
+            self.removed,
+            self.moved_removed,
+            self.moved_added,
+        ];
+        if markers.iter().any(|marker| marker.is_control()) {
+            return Err(InvalidMarkers(format!(
+                "markers must not be control characters, got {markers:?}"
+            )));
+        }
+        let removed = [self.removed, self.moved_removed];
+        let added = [self.added, self.moved_added];
+        if removed.contains(&self.context)
+            || added.contains(&self.context)
+            || removed.iter().any(|marker| added.contains(marker))
+        {
+            return Err(InvalidMarkers(format!(
+                "markers must be distinct, got {markers:?}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Markers {
+    fn default() -> Self {
+        Markers::UNIFIED
+    }
+}
+
+/// Describes why `Markers::validate` rejected a set of markers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidMarkers(pub String);
+
+impl fmt::Display for InvalidMarkers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid markers: {}", self.0)
+    }
+}
+
+impl Error for InvalidMarkers {}
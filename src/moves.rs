@@ -0,0 +1,75 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::heuristics::HeuristicPipeline;
+use crate::{ChunkBlock, PatchSet};
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+
+impl<'a> PatchSet<'a> {
+    /// Marks blocks that only remove lines and blocks that only add the same lines, after
+    /// normalization with `pipeline`, as `ChunkBlock::Moved`, so moved code can be told apart
+    /// from code that was really deleted or added. The two blocks may be in the same file or in
+    /// different ones. Each removal pairs with at most one addition, the first unpaired one in
+    /// patch order. Blocks that both remove and add lines are never considered, so code that was
+    /// edited while being moved still shows as a change.
+    pub fn detect_moves(mut self, pipeline: &HeuristicPipeline) -> PatchSet<'a> {
+        // Pure removals and additions by their normalized text, as (file, chunk, block) indices.
+        let mut removals: HashMap<String, VecDeque<(usize, usize, usize)>> = HashMap::new();
+        let mut additions = Vec::new();
+        for (i, file_diff) in self.files.iter().enumerate() {
+            for (j, chunk) in file_diff.chunks.iter().enumerate() {
+                for (k, block) in chunk.blocks.iter().enumerate() {
+                    let ChunkBlock::Changed(changed) = block else {
+                        continue;
+                    };
+                    let (lines, removal) = if changed.is_pure_deletion() {
+                        (&changed.removed, true)
+                    } else if changed.is_pure_addition() {
+                        (&changed.added, false)
+                    } else {
+                        continue;
+                    };
+                    // Where a diff puts the blank line between two functions is arbitrary, so it
+                    // should not keep a move from being detected.
+                    let text = pipeline.apply(lines).trim().to_owned();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if removal {
+                        removals.entry(text).or_default().push_back((i, j, k));
+                    } else {
+                        additions.push((text, (i, j, k)));
+                    }
+                }
+            }
+        }
+
+        let mut moved = Vec::new();
+        for (text, addition) in additions {
+            if let Some(removal) = removals.get_mut(&text).and_then(VecDeque::pop_front) {
+                moved.extend([removal, addition]);
+            }
+        }
+        for (i, j, k) in moved {
+            let block = &mut self.files[i].chunks[j].blocks[k];
+            if let ChunkBlock::Changed(changed) =
+                mem::replace(block, ChunkBlock::Note(String::new()))
+            {
+                *block = ChunkBlock::Moved(changed);
+            }
+        }
+        self
+    }
+}
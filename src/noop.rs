@@ -0,0 +1,56 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Chunk, ChunkBlock, ElisionReason, ProcessConfig, Stats};
+use std::collections::HashMap;
+use std::mem;
+
+/// Elides pairs of surviving changed blocks in one file that undo each other: one removes what
+/// the other adds and adds what the other removes, after normalization. This catches a change
+/// that is reverted in a later hunk, as well as lines deleted in one place and re-added
+/// verbatim in another.
+///
+/// Strictly speaking, the file still changes: the two places swap their contents, or lines move.
+/// That is why this is opt-in.
+///
+/// Pairs are matched greedily in file order, so with several candidates a block pairs with the
+/// nearest unmatched inverse before it.
+pub(crate) fn elide_net_noops(chunks: &mut [Chunk], config: &ProcessConfig, stats: &mut Stats) {
+    // Blocks still waiting for their inverse, keyed by their normalized (removed, added) sides.
+    let mut unmatched: HashMap<(String, String), Vec<(usize, usize)>> = HashMap::new();
+    let mut paired = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        for (j, block) in chunk.blocks.iter().enumerate() {
+            let ChunkBlock::Changed(changed) = block else {
+                continue;
+            };
+            let removed = config.pipeline.apply(&changed.removed);
+            let added = config.pipeline.apply(&changed.added);
+            match unmatched
+                .get_mut(&(added.clone(), removed.clone()))
+                .and_then(Vec::pop)
+            {
+                Some(inverse) => paired.extend([inverse, (i, j)]),
+                None => unmatched.entry((removed, added)).or_default().push((i, j)),
+            }
+        }
+    }
+    for (i, j) in paired {
+        let block = &mut chunks[i].blocks[j];
+        if let ChunkBlock::Changed(changed) = mem::replace(block, ChunkBlock::Context(Vec::new())) {
+            stats.record_elision(&ElisionReason::NetNoop, &[]);
+            *block = ChunkBlock::Elided(changed, ElisionReason::NetNoop);
+        }
+    }
+}
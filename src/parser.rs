@@ -0,0 +1,87 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::format_detector::{detect_diff_format, DiffFormat};
+use crate::{check_file_headers, parse, DiffParseError, FileDiff, ParseErrorPolicy};
+
+/// Options for `DiffParser`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// How malformed input is handled. With `ParseErrorPolicy::Fail`, the first problem
+    /// `find_parse_errors` would report is returned.
+    pub error_policy: ParseErrorPolicy,
+    /// Keep `\ No newline at end of file` markers with the line they follow, so they are emitted
+    /// again and the line still lacks a newline when the result is applied. Otherwise they are
+    /// dropped.
+    pub preserve_no_newline_markers: bool,
+    /// Check the format of the input with `detect_diff_format` before parsing it, and report a
+    /// problem if it is not a unified diff.
+    pub detect_format: bool,
+    /// Stop after this many files, ignoring the rest of the input.
+    pub max_file_diffs: Option<usize>,
+}
+
+/// Splits a unified diff into per-file diffs, like `parse_file_diffs`, with the behavior set by a
+/// `ParserConfig`.
+#[derive(Clone, Debug, Default)]
+pub struct DiffParser {
+    config: ParserConfig,
+}
+
+impl DiffParser {
+    /// Creates a parser with the default config, which behaves like `parse_file_diffs`.
+    pub fn new() -> Self {
+        DiffParser::default()
+    }
+
+    pub fn with_config(config: ParserConfig) -> Self {
+        DiffParser { config }
+    }
+
+    pub fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
+    pub fn parse<'a>(&self, input: &'a str) -> Result<Vec<FileDiff<'a>>, DiffParseError> {
+        let mut errors = Vec::new();
+        if self.config.detect_format {
+            let format = detect_diff_format(input);
+            if matches!(format, DiffFormat::Ed | DiffFormat::Unknown) {
+                errors.push(DiffParseError {
+                    line: input.lines().next().unwrap_or_default().to_owned(),
+                    message: format!("unsupported diff format ({format})"),
+                    line_number: Some(1),
+                    byte_offset: Some(0),
+                });
+            }
+        }
+        let file_diffs = parse(input, &self.config, &mut errors);
+        if self.config.error_policy != ParseErrorPolicy::Ignore {
+            check_file_headers(input, &file_diffs, &mut errors);
+        }
+        match self.config.error_policy {
+            ParseErrorPolicy::Fail => match errors.into_iter().next() {
+                Some(error) => Err(error),
+                None => Ok(file_diffs),
+            },
+            ParseErrorPolicy::Warn => {
+                for error in errors {
+                    log::warn!("{error}");
+                }
+                Ok(file_diffs)
+            }
+            ParseErrorPolicy::Ignore => Ok(file_diffs),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::preview::append_note;
+use crate::{Chunk, FileDiff, Markers, OverlappingChunks};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// An ordered collection of per-file diffs, e.g. everything parsed from one input.
+#[derive(Clone, Debug, Default)]
+pub struct PatchSet<'a> {
+    pub files: Vec<FileDiff<'a>>,
+}
+
+impl<'a> PatchSet<'a> {
+    /// Keeps only the files for which `predicate` returns `true`.
+    pub fn retain_files(mut self, predicate: impl Fn(&FileDiff) -> bool) -> Self {
+        self.files.retain(|file| predicate(file));
+        self
+    }
+
+    /// Returns the number of added lines minus the number of removed lines across all files, as
+    /// counted by `FileDiff::total_changed_lines`.
+    pub fn total_net_delta(&self) -> i64 {
+        self.files
+            .iter()
+            .map(|file| {
+                let (removed, added) = file.total_changed_lines();
+                added as i64 - removed as i64
+            })
+            .sum()
+    }
+
+    /// Groups the files by the extension of `FileDiff::file_name()`, e.g. `"cc"` or `"py"`, keeping
+    /// their relative order. Files without an extension are grouped under `""`.
+    pub fn split_by_file_extension(self) -> HashMap<String, PatchSet<'a>> {
+        let mut groups: HashMap<String, PatchSet> = HashMap::new();
+        for file_diff in self {
+            let extension = Path::new(file_diff.file_name())
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            groups.entry(extension).or_default().files.push(file_diff);
+        }
+        groups
+    }
+
+    /// Shows each chunk that appears in several files only once: the first occurrence gets a note
+    /// listing the other files, and the rest are dropped, along with any file left empty. Chunks
+    /// are compared by their lines, ignoring the `@@` line and trailing whitespace.
+    ///
+    /// The notes are rendered as context lines, so the result is for review and does not apply.
+    pub fn group_identical_hunks(self) -> PatchSet<'a> {
+        fn body(chunk: &Chunk) -> String {
+            chunk
+                .blocks
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>()
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        // The first occurrence of each distinct body, and the other files it appears in.
+        let mut first_occurrences = HashMap::new();
+        let mut also_in: HashMap<(usize, usize), Vec<String>> = HashMap::new();
+        let mut duplicates = HashSet::new();
+        for (i, file_diff) in self.files.iter().enumerate() {
+            for (j, chunk) in file_diff.chunks.iter().enumerate() {
+                match first_occurrences.entry(body(chunk)) {
+                    Entry::Vacant(entry) => {
+                        entry.insert((i, j));
+                    }
+                    Entry::Occupied(entry) => {
+                        let file_names = also_in.entry(*entry.get()).or_default();
+                        let file_name = file_diff.file_name().to_owned();
+                        if !file_names.contains(&file_name) {
+                            file_names.push(file_name);
+                        }
+                        duplicates.insert((i, j));
+                    }
+                }
+            }
+        }
+
+        let files = self
+            .files
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, FileDiff { header, chunks })| {
+                let chunks = chunks
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(j, _)| !duplicates.contains(&(i, *j)))
+                    .map(|(j, chunk)| match also_in.remove(&(i, j)) {
+                        Some(file_names) => append_note(
+                            chunk,
+                            format!(
+                                "[diffreducer: identical hunk also in {}]",
+                                file_names.join(", ")
+                            ),
+                        ),
+                        None => chunk,
+                    })
+                    .collect::<Vec<_>>();
+                (!chunks.is_empty()).then_some(FileDiff { header, chunks })
+            })
+            .collect();
+        PatchSet { files }
+    }
+
+    /// Splits every file with several chunks into one file per chunk, each with a copy of the
+    /// original file header, so that each chunk is a standalone patch.
+    pub fn ungrouped(self) -> PatchSet<'a> {
+        let files = self
+            .files
+            .into_iter()
+            .flat_map(|FileDiff { header, chunks }| {
+                chunks.into_iter().map(move |chunk| FileDiff {
+                    header: header.clone(),
+                    chunks: vec![chunk],
+                })
+            })
+            .collect();
+        PatchSet { files }
+    }
+
+    /// Combines two patch sets. Diffs for the same file are merged into one, with their chunks
+    /// sorted by start line and a chunk that both sets contain kept once; the header is taken from
+    /// whichever set mentioned the file first.
+    pub fn merge(a: PatchSet<'a>, b: PatchSet<'a>) -> PatchSet<'a> {
+        let mut order = Vec::new();
+        let mut headers = HashMap::new();
+        let mut chunks_by_file: HashMap<String, Vec<Chunk>> = HashMap::new();
+        for file_diff in a.into_iter().chain(b) {
+            let file_name = file_diff.file_name().to_owned();
+            let FileDiff { header, chunks } = file_diff;
+            if !headers.contains_key(&file_name) {
+                order.push(file_name.clone());
+                headers.insert(file_name.clone(), header);
+            }
+            chunks_by_file.entry(file_name).or_default().extend(chunks);
+        }
+        let files = order
+            .into_iter()
+            .map(|file_name| {
+                let mut chunks = chunks_by_file.remove(&file_name).unwrap_or_default();
+                chunks.sort_by_key(|chunk| chunk.start_line());
+                chunks.dedup_by(|a, b| a.to_string() == b.to_string());
+                FileDiff {
+                    header: headers.remove(&file_name).unwrap_or_default(),
+                    chunks,
+                }
+            })
+            .collect();
+        PatchSet { files }
+    }
+
+    /// Computes the files, and within them the chunks, that differ between two patch sets, e.g.
+    /// the results of reducing the same input with two different configs. Trailing whitespace and
+    /// line endings are ignored when comparing.
+    ///
+    /// The differing chunks of a file in both sets are combined into one file diff, so if a chunk
+    /// from one side overlaps a chunk from the other, e.g. because both touch the same lines
+    /// differently, the result would not be a valid diff and that is returned as an error instead.
+    pub fn diff(a: &PatchSet<'a>, b: &PatchSet<'a>) -> Result<PatchSet<'a>, OverlappingChunks> {
+        fn normalize(text: impl fmt::Display) -> String {
+            text.to_string()
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        fn differing_chunks<'a>(from: &FileDiff<'a>, other: &FileDiff) -> Vec<Chunk<'a>> {
+            let other_chunks = other.chunks.iter().map(normalize).collect::<HashSet<_>>();
+            from.chunks
+                .iter()
+                .filter(|chunk| !other_chunks.contains(&normalize(chunk)))
+                .cloned()
+                .collect()
+        }
+
+        fn find<'s, 'a>(set: &'s PatchSet<'a>, file_name: &str) -> Option<&'s FileDiff<'a>> {
+            set.files.iter().find(|file| file.file_name() == file_name)
+        }
+
+        let mut files = Vec::new();
+        for file in &a.files {
+            match find(b, file.file_name()) {
+                None => files.push(file.clone()),
+                Some(other) if normalize(file) == normalize(other) => (),
+                Some(other) => {
+                    let mut chunks = differing_chunks(file, other);
+                    chunks.extend(differing_chunks(other, file));
+                    files.push(if chunks.is_empty() {
+                        // Only the file headers differ.
+                        file.clone()
+                    } else {
+                        let mut file_diff = FileDiff {
+                            header: file.header.clone(),
+                            chunks,
+                        };
+                        file_diff.sort_chunks()?;
+                        file_diff
+                    });
+                }
+            }
+        }
+        files.extend(
+            b.files
+                .iter()
+                .filter(|file| find(a, file.file_name()).is_none())
+                .cloned(),
+        );
+        Ok(PatchSet { files })
+    }
+}
+
+impl<'a> From<Vec<FileDiff<'a>>> for PatchSet<'a> {
+    fn from(files: Vec<FileDiff<'a>>) -> Self {
+        PatchSet { files }
+    }
+}
+
+impl<'a> IntoIterator for PatchSet<'a> {
+    type Item = FileDiff<'a>;
+    type IntoIter = std::vec::IntoIter<FileDiff<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.into_iter()
+    }
+}
+
+impl<'a> PatchSet<'a> {
+    /// Renders the patch set like `Display`, but with custom line prefixes.
+    pub fn display_with<'s>(&'s self, markers: &'s Markers) -> impl fmt::Display + 's {
+        WithMarkers(self, markers)
+    }
+
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, markers: &Markers) -> fmt::Result {
+        for file in &self.files {
+            file.fmt_with(f, markers)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+struct WithMarkers<'s, 'a>(&'s PatchSet<'a>, &'s Markers);
+
+impl<'s, 'a> fmt::Display for WithMarkers<'s, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_with(f, self.1)
+    }
+}
+
+impl<'a> fmt::Display for PatchSet<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, &Markers::UNIFIED)
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Chunk, ChunkBlock, ChunkHeader, FileDiff};
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
+
+/// Truncates `file_diff` to its first `keep` chunks. If any were dropped, a note saying how many is
+/// appended to the last remaining chunk as a context line, and that chunk's header counts it.
+pub(crate) fn keep_first_chunks(file_diff: FileDiff, keep: NonZeroUsize) -> FileDiff {
+    let FileDiff { header, mut chunks } = file_diff;
+    let omitted = chunks.len().saturating_sub(keep.get());
+    if omitted > 0 {
+        chunks.truncate(keep.get());
+        let chunk = chunks.pop().unwrap();
+        let plural = if omitted == 1 { "" } else { "s" };
+        chunks.push(append_note(
+            chunk,
+            format!("[diffreducer: {omitted} more hunk{plural} omitted]"),
+        ));
+    }
+    FileDiff { header, chunks }
+}
+
+/// Follows each elided block in `file_diff` with a note saying that something was elided there,
+/// and if `with_reasons` is set, why. The chunk headers are updated to count the notes.
+pub(crate) fn annotate_elisions(file_diff: FileDiff, with_reasons: bool) -> FileDiff {
+    let FileDiff { header, chunks } = file_diff;
+    let chunks = chunks
+        .into_iter()
+        .map(|Chunk { header, blocks }| {
+            let mut notes = 0;
+            let mut annotated = Vec::with_capacity(blocks.len());
+            for block in blocks {
+                let note = match &block {
+                    ChunkBlock::Elided(_, reason) if with_reasons => {
+                        Some(format!("[diffreducer: elided ({reason})]"))
+                    }
+                    ChunkBlock::Elided(..) => Some("[diffreducer: elided]".to_owned()),
+                    _ => None,
+                };
+                annotated.push(block);
+                if let Some(note) = note {
+                    notes += 1;
+                    annotated.push(ChunkBlock::Note(note));
+                }
+            }
+            Chunk {
+                header: count_notes(header, notes),
+                blocks: annotated,
+            }
+        })
+        .collect();
+    FileDiff { header, chunks }
+}
+
+/// Appends `note` to the end of `chunk` as a context line, updating the header to count it.
+pub(crate) fn append_note(chunk: Chunk, note: String) -> Chunk {
+    let Chunk { header, mut blocks } = chunk;
+    blocks.push(ChunkBlock::Note(note));
+    Chunk {
+        header: count_notes(header, 1),
+        blocks,
+    }
+}
+
+/// Updates a chunk header to count `notes` more context lines.
+fn count_notes(header: Cow<str>, notes: u32) -> Cow<str> {
+    if notes == 0 {
+        return header;
+    }
+    match ChunkHeader::parse(&header) {
+        Some(mut chunk_header) => {
+            // An empty range is described by the line before it, so it moves along once the range
+            // is no longer empty.
+            chunk_header.old_start += u32::from(chunk_header.old_count == 0);
+            chunk_header.new_start += u32::from(chunk_header.new_count == 0);
+            chunk_header.old_count += notes;
+            chunk_header.new_count += notes;
+            Cow::Owned(format!("{chunk_header}\n"))
+        }
+        None => header,
+    }
+}
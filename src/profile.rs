@@ -0,0 +1,42 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// Normalization happens deep inside processing, so its time is accumulated globally rather than
+// threaded through every call.
+static NORMALIZATION_NANOS: AtomicU64 = AtomicU64::new(0);
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables timing of normalization. Timing is off by default.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns the total time spent normalizing changed blocks in this process so far. Take the
+/// difference between two calls to time a single run.
+pub fn normalization_time() -> Duration {
+    Duration::from_nanos(NORMALIZATION_NANOS.load(Ordering::Relaxed))
+}
+
+pub(crate) fn time_normalization<T>(f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    NORMALIZATION_NANOS.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    result
+}
@@ -0,0 +1,83 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ChunkBlock, ChunkHeader, FileDiff};
+use std::fmt;
+
+/// A context line of a diff that does not match the file it is meant to apply to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextMismatch {
+    pub file_name: String,
+    /// The 1-based line number in the original file.
+    pub line: u32,
+    /// The context line in the diff.
+    pub expected: String,
+    /// The line in the file, or `None` if the file is shorter.
+    pub found: Option<String>,
+}
+
+impl fmt::Display for ContextMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: context line {:?} does not match ",
+            self.file_name, self.line, self.expected
+        )?;
+        match &self.found {
+            Some(found) => write!(f, "{found:?}"),
+            None => write!(f, "the end of the file"),
+        }
+    }
+}
+
+impl FileDiff<'_> {
+    /// Compares every context line with the line at the same position in `original`, the
+    /// contents of the file before the change. Removed lines are only used to keep track of the
+    /// position, and chunks whose header cannot be parsed are skipped.
+    pub fn context_mismatches(&self, original: &str) -> Vec<ContextMismatch> {
+        let original = original.lines().collect::<Vec<_>>();
+        let mut mismatches = Vec::new();
+        for chunk in &self.chunks {
+            let Some(header) = ChunkHeader::parse(&chunk.header) else {
+                continue;
+            };
+            let mut line = header.old_start;
+            for block in &chunk.blocks {
+                match block {
+                    ChunkBlock::Context(lines) => {
+                        for expected in lines {
+                            let found = line
+                                .checked_sub(1)
+                                .and_then(|index| original.get(index as usize));
+                            if found != Some(expected) {
+                                mismatches.push(ContextMismatch {
+                                    file_name: self.file_name().to_owned(),
+                                    line,
+                                    expected: expected.to_string(),
+                                    found: found.map(|found| found.to_string()),
+                                });
+                            }
+                            line += 1;
+                        }
+                    }
+                    ChunkBlock::Changed(changed)
+                    | ChunkBlock::Elided(changed, _)
+                    | ChunkBlock::Moved(changed) => line += changed.removed.len() as u32,
+                    ChunkBlock::Note(_) => (),
+                }
+            }
+        }
+        mismatches
+    }
+}
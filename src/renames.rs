@@ -0,0 +1,241 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::similarity::jaccard_similarity;
+use crate::{Changed, Chunk, ChunkBlock, FileDiff};
+use std::borrow::Cow;
+
+/// Above this many cells in the table of `diff_lines`, a rename is not worth the memory, and the
+/// deletion and addition are kept as they are.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+/// A deleted file and an added file found by `detect_renames` to have similar content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameCandidate {
+    /// The index of the deleted file in the diffs passed to `detect_renames`.
+    pub deleted: usize,
+    /// The index of the added file.
+    pub added: usize,
+    pub from: String,
+    pub to: String,
+    /// The Jaccard similarity of the lines of both files, from 0 to 1.
+    pub similarity: f64,
+}
+
+impl<'a> FileDiff<'a> {
+    /// Returns whether the file is deleted, i.e. its new side is `/dev/null`.
+    pub fn is_deleted_file(&self) -> bool {
+        self.header.lines().any(|line| {
+            line.starts_with("deleted file mode ")
+                || line
+                    .strip_prefix("+++ /dev/null")
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('\t'))
+        })
+    }
+
+    /// Returns whether the file is added, i.e. its old side is `/dev/null`.
+    pub fn is_new_file(&self) -> bool {
+        self.header.lines().any(|line| {
+            line.starts_with("new file mode ")
+                || line
+                    .strip_prefix("--- /dev/null")
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('\t'))
+        })
+    }
+
+    /// Returns whether git reported the file as renamed or copied from another path, with
+    /// `rename from` and `rename to` or `copy from` and `copy to` lines.
+    pub fn is_rename_or_copy(&self) -> bool {
+        self.header
+            .lines()
+            .any(|line| line.starts_with("rename from ") || line.starts_with("copy from "))
+    }
+
+    /// Returns the removed or added lines of every changed block, in order.
+    fn changed_lines(&self, removed: bool) -> Vec<&'a str> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| &chunk.blocks)
+            .filter_map(|block| match block {
+                ChunkBlock::Changed(changed) => Some(if removed {
+                    &changed.removed
+                } else {
+                    &changed.added
+                }),
+                _ => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Pairs deleted files with added files whose content is similar, like `git diff
+/// --find-renames`. The content of a deleted file is its removed lines, and that of an added file
+/// its added lines. Each file is paired at most once, most similar pairs first; empty files and
+/// pairs without any line in common are never paired. The result is sorted by `deleted`.
+pub fn detect_renames(diffs: &[FileDiff]) -> Vec<RenameCandidate> {
+    let contents = |deleted: bool| {
+        diffs
+            .iter()
+            .enumerate()
+            .filter(|(_, file_diff)| {
+                if deleted {
+                    file_diff.is_deleted_file()
+                } else {
+                    file_diff.is_new_file()
+                }
+            })
+            .map(|(i, file_diff)| (i, file_diff.changed_lines(deleted)))
+            .filter(|(_, lines)| !lines.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let deleted = contents(true);
+    let added = contents(false);
+
+    let mut pairs = Vec::new();
+    for (i, old) in &deleted {
+        for (j, new) in &added {
+            let similarity = jaccard_similarity(old, new);
+            if similarity > 0.0 {
+                pairs.push((similarity, *i, *j));
+            }
+        }
+    }
+    // Most similar first, and in patch order among equals.
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+    let mut candidates: Vec<RenameCandidate> = Vec::new();
+    for (similarity, i, j) in pairs {
+        if candidates.iter().any(|c| c.deleted == i || c.added == j) {
+            continue;
+        }
+        candidates.push(RenameCandidate {
+            deleted: i,
+            added: j,
+            from: diffs[i].file_name().to_owned(),
+            to: diffs[j].file_name().to_owned(),
+            similarity,
+        });
+    }
+    candidates.sort_by_key(|candidate| candidate.deleted);
+    candidates
+}
+
+/// Replaces each pair found by `detect_renames` with at least `threshold` similarity by a single
+/// rename, in place of the added file. Unless the content is identical, the rename has one chunk
+/// covering the whole file, with the differences between the two versions as changed blocks, so
+/// they can be reduced like any other change.
+pub(crate) fn collapse_renames<'a>(
+    file_diffs: Vec<FileDiff<'a>>,
+    threshold: f64,
+) -> Vec<FileDiff<'a>> {
+    let mut renames = Vec::new();
+    for candidate in detect_renames(&file_diffs) {
+        if candidate.similarity < threshold {
+            continue;
+        }
+        let old = file_diffs[candidate.deleted].changed_lines(true);
+        let new = file_diffs[candidate.added].changed_lines(false);
+        if let Some(rename) = rename(&candidate, &old, &new) {
+            renames.push((candidate.deleted, candidate.added, rename));
+        }
+    }
+    let mut file_diffs = file_diffs.into_iter().map(Some).collect::<Vec<_>>();
+    for (deleted, added, rename) in renames {
+        file_diffs[deleted] = None;
+        file_diffs[added] = Some(rename);
+    }
+    file_diffs.into_iter().flatten().collect()
+}
+
+fn rename<'a>(
+    candidate: &RenameCandidate,
+    old: &[&'a str],
+    new: &[&'a str],
+) -> Option<FileDiff<'a>> {
+    let RenameCandidate { from, to, .. } = candidate;
+    let mut header = format!(
+        "diff --git a/{from} b/{to}\nsimilarity index {}%\nrename from {from}\nrename to {to}\n",
+        (candidate.similarity * 100.0).floor()
+    );
+    if old == new {
+        return Some(FileDiff {
+            header: Cow::Owned(header),
+            chunks: Vec::new(),
+        });
+    }
+    let blocks = diff_lines(old, new)?;
+    header.push_str(&format!("--- a/{from}\n+++ b/{to}\n"));
+    Some(FileDiff {
+        header: Cow::Owned(header),
+        chunks: vec![Chunk {
+            header: Cow::Owned(format!("@@ -1,{} +1,{} @@\n", old.len(), new.len())),
+            blocks,
+        }],
+    })
+}
+
+/// Aligns two versions of a file along their longest common subsequence of lines. Returns `None`
+/// if the files are too large to compare.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Option<Vec<ChunkBlock<'a>>> {
+    let width = new.len() + 1;
+    if (old.len() + 1).checked_mul(width)? > MAX_DIFF_CELLS {
+        return None;
+    }
+    // `lengths[i * width + j]` is the length of the longest common subsequence of `old[i..]` and
+    // `new[j..]`.
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            match blocks.last_mut() {
+                Some(ChunkBlock::Context(lines)) => lines.push(old[i]),
+                _ => blocks.push(ChunkBlock::Context(vec![old[i]])),
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+        if !matches!(blocks.last(), Some(ChunkBlock::Changed(_))) {
+            blocks.push(ChunkBlock::Changed(Changed {
+                removed: Vec::new(),
+                added: Vec::new(),
+            }));
+        }
+        let Some(ChunkBlock::Changed(changed)) = blocks.last_mut() else {
+            unreachable!();
+        };
+        if j == new.len()
+            || i < old.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]
+        {
+            changed.removed.push(old[i]);
+            i += 1;
+        } else {
+            changed.added.push(new[j]);
+            j += 1;
+        }
+    }
+    Some(blocks)
+}
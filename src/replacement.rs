@@ -0,0 +1,343 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ProcessConfig;
+use aho_corasick::AhoCorasick;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct Replacement {
+    /// The text to replace, or the pattern to match if `pattern` is set.
+    pub before: Cow<'static, str>,
+    /// The text to replace it with. If `pattern` is set, `$1` or `${1}` stands for what the first
+    /// capture group matched, and so on.
+    pub after: Cow<'static, str>,
+    /// `before` compiled as a regular expression, for replacements created by `regex`.
+    pub pattern: Option<Regex>,
+}
+
+pub(crate) const REPLACEMENTS: &[Replacement] = &[Replacement {
+    before: Cow::Borrowed("NOTREACHED_NORETURN"),
+    after: Cow::Borrowed("NOTREACHED"),
+    pattern: None,
+}];
+
+impl Replacement {
+    pub fn new(before: impl Into<Cow<'static, str>>, after: impl Into<Cow<'static, str>>) -> Self {
+        Replacement {
+            before: before.into(),
+            after: after.into(),
+            pattern: None,
+        }
+    }
+
+    /// Creates a replacement that rewrites every match of the regular expression `before`,
+    /// compiled with `ProcessConfig::compile_filter_pattern`. Fails if it does not compile.
+    pub fn regex(
+        before: impl Into<Cow<'static, str>>,
+        after: impl Into<Cow<'static, str>>,
+    ) -> Result<Self, ReplacementValidationError> {
+        let before = before.into();
+        let pattern = ProcessConfig::compile_filter_pattern(&before).map_err(|error| {
+            ReplacementValidationError {
+                before: before.to_string(),
+                message: format!("invalid pattern: {error}"),
+            }
+        })?;
+        Ok(Replacement {
+            before,
+            after: after.into(),
+            pattern: Some(pattern),
+        })
+    }
+
+    /// Returns whether the replacement would change anything in `text`.
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => pattern.is_match(text),
+            None => text.contains(&*self.before),
+        }
+    }
+
+    pub(crate) fn apply(&self, text: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern.replace_all(text, &*self.after).into_owned(),
+            None => text.replace(&*self.before, &self.after),
+        }
+    }
+
+    /// Checks that the replacement can do something sensible: both sides are non-empty, and
+    /// `after` only refers to capture groups (`$1`, `${1}`) that `pattern` has, which a literal
+    /// `before` has none of.
+    pub fn validate(&self) -> Result<(), ReplacementValidationError> {
+        static BACK_REFERENCE_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"\$(\d+|\{\d+\})").unwrap());
+        let error = |message: String| {
+            Err(ReplacementValidationError {
+                before: self.before.to_string(),
+                message,
+            })
+        };
+        if self.before.is_empty() {
+            return error("`before` is empty".to_owned());
+        }
+        if self.after.is_empty() {
+            return error("`after` is empty".to_owned());
+        }
+        let Some(pattern) = &self.pattern else {
+            if let Some(back_reference) = BACK_REFERENCE_RE.find(&self.after) {
+                return error(format!(
+                    "`after` refers to capture group {} but `before` is not a pattern",
+                    back_reference.as_str()
+                ));
+            }
+            return Ok(());
+        };
+        for back_reference in BACK_REFERENCE_RE.captures_iter(&self.after) {
+            let group = back_reference[1].trim_matches(['{', '}']);
+            if !group
+                .parse::<usize>()
+                .is_ok_and(|group| group < pattern.captures_len())
+            {
+                return error(format!(
+                    "`after` refers to capture group {} but the pattern has {} groups",
+                    &back_reference[0],
+                    pattern.captures_len() - 1
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds every `before` of a list of replacements in a single pass over the text, instead of
+/// searching for each in turn.
+#[derive(Clone, Debug)]
+pub struct ReplacementMatcher {
+    automaton: AhoCorasick,
+    /// The index in the replacements of each pattern of `automaton`. Replacements with a
+    /// `pattern` are matched on their own.
+    literals: Vec<usize>,
+}
+
+impl ReplacementMatcher {
+    /// Fails if the automaton would be too large.
+    pub fn new(replacements: &[Replacement]) -> Result<Self, aho_corasick::BuildError> {
+        let literals = (0..replacements.len())
+            .filter(|&i| replacements[i].pattern.is_none())
+            .collect::<Vec<_>>();
+        let automaton = AhoCorasick::new(literals.iter().map(|&i| &*replacements[i].before))?;
+        Ok(ReplacementMatcher {
+            automaton,
+            literals,
+        })
+    }
+
+    /// Applies `replacements`, which must be the ones the matcher was built from, in order, each
+    /// to the result of the previous ones, returning the result and the replacements that
+    /// changed something. Until one does, the text is unchanged, so a single scan tells which
+    /// replacements to try; after that, each is searched for individually.
+    pub(crate) fn apply<'r>(
+        &self,
+        text: String,
+        replacements: &'r [Replacement],
+    ) -> (String, Vec<&'r Replacement>) {
+        let mut present = vec![false; replacements.len()];
+        for found in self.automaton.find_overlapping_iter(&text) {
+            present[self.literals[found.pattern().as_usize()]] = true;
+        }
+        let mut applied = Vec::new();
+        let text =
+            replacements
+                .iter()
+                .zip(present)
+                .fold(text, |current, (replacement, present)| {
+                    let found = if applied.is_empty() && replacement.pattern.is_none() {
+                        present
+                    } else {
+                        replacement.is_match(&current)
+                    };
+                    if !found {
+                        return current;
+                    }
+                    applied.push(replacement);
+                    replacement.apply(&current)
+                });
+        (text, applied)
+    }
+}
+
+/// A named collection of replacements that are used together, e.g. for one migration.
+#[derive(Clone, Debug)]
+pub struct ReplacementSet {
+    /// Identifies the revision of the set, to tell which rules a result was produced with.
+    pub version: String,
+    pub replacements: Vec<Replacement>,
+}
+
+/// The replacement sets a `ProcessConfig` can refer to by name.
+#[derive(Clone, Debug)]
+pub struct ReplacementRegistry {
+    pub rules: HashMap<String, ReplacementSet>,
+}
+
+impl ReplacementRegistry {
+    /// The name of the built-in set, which is used unless another one is chosen.
+    pub const DEFAULT_SET: &'static str = "chromium-notreached";
+
+    /// Creates a registry without any sets, not even the built-in one.
+    pub fn new() -> Self {
+        ReplacementRegistry {
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Adds a set, returning the one previously registered under `name`, if any.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        version: impl Into<String>,
+        replacements: Vec<Replacement>,
+    ) -> Option<ReplacementSet> {
+        self.rules.insert(
+            name.into(),
+            ReplacementSet {
+                version: version.into(),
+                replacements,
+            },
+        )
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ReplacementSet> {
+        self.rules.get(name)
+    }
+
+    /// Returns the names of all registered sets, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.rules.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl Default for ReplacementRegistry {
+    /// Creates a registry with only the built-in set.
+    fn default() -> Self {
+        let mut registry = ReplacementRegistry::new();
+        registry.register(ReplacementRegistry::DEFAULT_SET, "1", REPLACEMENTS.to_vec());
+        registry
+    }
+}
+
+impl fmt::Display for Replacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pattern {
+            Some(_) => write!(f, "/{}/ -> {}", self.before, self.after),
+            None => write!(f, "{} -> {}", self.before, self.after),
+        }
+    }
+}
+
+/// Reads replacements from a TOML file like
+///
+/// ```toml
+/// [[rules]]
+/// before = "NOTREACHED_NORETURN"
+/// after = "NOTREACHED"
+/// ```
+///
+/// in order. An entry with `regex = true` is a pattern, see `Replacement::regex`. A file without
+/// any `rules` has no replacements.
+pub fn rules_from_toml(rules: &str) -> Result<Vec<Replacement>, RulesError> {
+    let rules = rules
+        .parse::<toml::Table>()
+        .map_err(|error| RulesError(error.to_string()))?;
+    if let Some(key) = rules.keys().find(|key| key.as_str() != "rules") {
+        return Err(RulesError(format!("unknown key `{key}`")));
+    }
+    let Some(entries) = rules.get("rules") else {
+        return Ok(Vec::new());
+    };
+    let entries = entries
+        .as_array()
+        .ok_or_else(|| RulesError("`rules` is not an array of tables".to_owned()))?;
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let error = |message: String| RulesError(format!("`rules[{i}]`: {message}"));
+            let entry = entry
+                .as_table()
+                .ok_or_else(|| error("not a table".to_owned()))?;
+            if let Some(key) = entry
+                .keys()
+                .find(|key| !["before", "after", "regex"].contains(&key.as_str()))
+            {
+                return Err(error(format!("unknown key `{key}`")));
+            }
+            let field = |key: &str| match entry.get(key) {
+                Some(value) => value
+                    .as_str()
+                    .map(str::to_owned)
+                    .ok_or_else(|| error(format!("`{key}` is not a string"))),
+                None => Err(error(format!("missing `{key}`"))),
+            };
+            let regex = match entry.get("regex") {
+                Some(value) => value
+                    .as_bool()
+                    .ok_or_else(|| error("`regex` is not a boolean".to_owned()))?,
+                None => false,
+            };
+            if regex {
+                Replacement::regex(field("before")?, field("after")?)
+                    .map_err(|invalid| error(invalid.message))
+            } else {
+                Ok(Replacement::new(field("before")?, field("after")?))
+            }
+        })
+        .collect()
+}
+
+/// Describes why `rules_from_toml` failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RulesError(pub String);
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rules: {}", self.0)
+    }
+}
+
+impl Error for RulesError {}
+
+/// Describes why a `Replacement` was rejected by `Replacement::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplacementValidationError {
+    /// The `before` side of the offending replacement, to identify it.
+    pub before: String,
+    pub message: String,
+}
+
+impl fmt::Display for ReplacementValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid replacement {:?}: {}", self.before, self.message)
+    }
+}
+
+impl Error for ReplacementValidationError {}
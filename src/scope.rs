@@ -0,0 +1,105 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Changed, Chunk, ChunkBlock, ElisionReason, Stats};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::mem;
+
+enum WrapperLine {
+    Open,
+    Close,
+    Blank,
+}
+
+fn classify(line: &str) -> Option<WrapperLine> {
+    // namespace foo {
+    // inline namespace v2 {
+    // extern "C" {
+    static OPEN_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"^\s*(?:(?:inline\s+)?namespace(?:\s+[\w:]+)?|extern\s+"C")\s*\{\s*$"#)
+            .unwrap()
+    });
+    // }  // namespace foo
+    static CLOSE_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^\s*\}\s*(?://.*|/\*.*\*/\s*)?$").unwrap());
+    if line.trim().is_empty() {
+        Some(WrapperLine::Blank)
+    } else if OPEN_RE.is_match(line) {
+        Some(WrapperLine::Open)
+    } else if CLOSE_RE.is_match(line) {
+        Some(WrapperLine::Close)
+    } else {
+        None
+    }
+}
+
+/// If `changed` only adds scope wrapper lines, returns the lowest depth reached within it and the
+/// depth at its end, both relative to its start.
+fn wrapper_depth(changed: &Changed) -> Option<(i32, i32)> {
+    if !changed.is_pure_addition() {
+        return None;
+    }
+    let (mut min, mut depth, mut scope_lines) = (0, 0, 0);
+    for line in &changed.added {
+        match classify(line)? {
+            WrapperLine::Open => depth += 1,
+            WrapperLine::Close => depth -= 1,
+            WrapperLine::Blank => continue,
+        }
+        min = min.min(depth);
+        scope_lines += 1;
+    }
+    (scope_lines > 0).then_some((min, depth))
+}
+
+/// Elides blocks that only add lines opening or closing a namespace (or `extern "C"`) scope, e.g.
+/// when existing code is moved into a new namespace. Re-indenting the wrapped code is left to the
+/// whitespace heuristics.
+///
+/// Only the diff is available, so braces are matched within it: the wrapper blocks of a file are
+/// elided only if, taken in order, every closing brace matches an earlier opening line and every
+/// opening line is closed. Otherwise, e.g. if the closing brace is in an unchanged line or falls
+/// outside the diff's context, all of them are kept. A wrapper line that is part of a larger
+/// changed block, such as one that also modifies code, is never elided.
+pub(crate) fn elide_scope_wrappers(chunks: &mut [Chunk], stats: &mut Stats) {
+    let mut depth = 0;
+    for block in chunks.iter().flat_map(|chunk| &chunk.blocks) {
+        let ChunkBlock::Changed(changed) = block else {
+            continue;
+        };
+        if let Some((min, end)) = wrapper_depth(changed) {
+            if depth + min < 0 {
+                return;
+            }
+            depth += end;
+        }
+    }
+    if depth != 0 {
+        return;
+    }
+    for chunk in chunks {
+        chunk.blocks = mem::take(&mut chunk.blocks)
+            .into_iter()
+            .map(|block| match block {
+                ChunkBlock::Changed(changed) if wrapper_depth(&changed).is_some() => {
+                    stats.changed_blocks += 1;
+                    stats.record_elision(&ElisionReason::ScopeWrapper, &[]);
+                    ChunkBlock::Elided(changed, ElisionReason::ScopeWrapper)
+                }
+                _ => block,
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Computes the Levenshtein distance between two sequences, i.e. the minimum number of
+/// insertions, deletions and substitutions of whole elements needed to turn `a` into `b`.
+pub fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // Only the previous row of the DP table is needed to compute the next one.
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_item) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_item) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_item != b_item);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Computes the Jaccard similarity `|a ∩ b| / |a ∪ b|` of two bags of tokens, where a token that
+/// appears several times counts that many times. Order does not matter, which makes this suited
+/// to spotting reordered imports. Two empty bags are considered identical.
+pub fn jaccard_similarity(a: &[&str], b: &[&str]) -> f64 {
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for token in a {
+        counts.entry(token).or_default().0 += 1;
+    }
+    for token in b {
+        counts.entry(token).or_default().1 += 1;
+    }
+    let (intersection, union) = counts
+        .values()
+        .fold((0, 0), |(intersection, union), &(a, b)| {
+            (intersection + a.min(b), union + a.max(b))
+        });
+    if union == 0 {
+        1.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
+
+/// Returns the length of the longest common subsequence of two sequences.
+pub fn lcs_length<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous = vec![0; b.len() + 1];
+    let mut current = vec![0; b.len() + 1];
+    for a_item in a {
+        for (j, b_item) in b.iter().enumerate() {
+            current[j + 1] = if a_item == b_item {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How the two sides of a changed block are compared once they have been normalized and the
+/// replacements applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimilarityMetric {
+    /// The sides must be identical.
+    #[default]
+    Exact,
+    /// One minus the token-level edit distance, relative to the longer side.
+    Levenshtein,
+    /// One minus `Changed::levenshtein_distance`, relative to the longer side. Whole lines are
+    /// compared as they appear in the diff, before normalization and replacements.
+    LineLevenshtein,
+    /// `jaccard_similarity` of the tokens, ignoring their order.
+    Jaccard,
+    /// Twice the longest common subsequence of tokens, relative to the total number of tokens.
+    Lcs,
+}
+
+impl SimilarityMetric {
+    pub const ALL: &'static [SimilarityMetric] = &[
+        SimilarityMetric::Exact,
+        SimilarityMetric::Levenshtein,
+        SimilarityMetric::LineLevenshtein,
+        SimilarityMetric::Jaccard,
+        SimilarityMetric::Lcs,
+    ];
+
+    /// Scores how similar two token sequences are, from 0 (nothing in common) to 1 (identical).
+    /// For `LineLevenshtein`, `a` and `b` are the lines of each side instead.
+    pub fn similarity(&self, a: &[&str], b: &[&str]) -> f64 {
+        let longest = a.len().max(b.len());
+        if longest == 0 {
+            return 1.0;
+        }
+        match self {
+            SimilarityMetric::Exact => f64::from(u8::from(a == b)),
+            SimilarityMetric::Levenshtein | SimilarityMetric::LineLevenshtein => {
+                1.0 - levenshtein(a, b) as f64 / longest as f64
+            }
+            SimilarityMetric::Jaccard => jaccard_similarity(a, b),
+            SimilarityMetric::Lcs => 2.0 * lcs_length(a, b) as f64 / (a.len() + b.len()) as f64,
+        }
+    }
+}
+
+impl fmt::Display for SimilarityMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimilarityMetric::Exact => write!(f, "exact"),
+            SimilarityMetric::Levenshtein => write!(f, "levenshtein"),
+            SimilarityMetric::LineLevenshtein => write!(f, "line-levenshtein"),
+            SimilarityMetric::Jaccard => write!(f, "jaccard"),
+            SimilarityMetric::Lcs => write!(f, "lcs"),
+        }
+    }
+}
+
+impl FromStr for SimilarityMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SimilarityMetric::ALL
+            .iter()
+            .find(|metric| metric.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                let metrics = SimilarityMetric::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown similarity metric {s:?}; expected one of {}",
+                    metrics.join(", ")
+                )
+            })
+    }
+}
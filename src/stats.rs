@@ -0,0 +1,190 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ContextMismatch, ElisionReason, FileDiff, Replacement};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Counters collected by `process_file_diffs_with_stats`.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    /// Files in the input, whether or not they survived.
+    pub files: usize,
+    /// Chunks in the input, across all files.
+    pub chunks: usize,
+    /// A breakdown of `changed_blocks` and `elided_blocks` for each file in the input.
+    pub per_file: Vec<FileStats>,
+    pub changed_blocks: usize,
+    pub elided_blocks: usize,
+    /// How many elided blocks each rule was responsible for, keyed by a label such as
+    /// `whitespace-only` or `replacement NOTREACHED_NORETURN -> NOTREACHED`. A block elided by
+    /// applying several replacements counts towards each of them.
+    pub elisions_by_rule: BTreeMap<String, usize>,
+    pub elisions_by_reason: BTreeMap<ElisionReason, usize>,
+    /// Files left in the output.
+    pub output_files: usize,
+    pub output_chunks: usize,
+    pub output_context_lines: usize,
+    pub output_removed_lines: usize,
+    pub output_added_lines: usize,
+    /// The first mismatch in each file passed through because of
+    /// `ProcessConfig::require_context_match`.
+    pub context_mismatches: Vec<ContextMismatch>,
+}
+
+/// The counters in `Stats` for a single file.
+#[derive(Clone, Debug, Default)]
+pub struct FileStats {
+    pub file_name: String,
+    pub changed_blocks: usize,
+    pub elided_blocks: usize,
+    /// Whether the file is in the output.
+    pub kept: bool,
+    /// The replacements found in the file before reduction, by their `before` side, in sorted
+    /// order. See `FileDiff::replaced_symbols`.
+    pub matched_rules: Vec<String>,
+}
+
+impl Stats {
+    /// Returns a one-line summary, e.g. `14/37 blocks filtered across 5/12 files`.
+    pub fn display_summary(&self) -> String {
+        Summary(self).to_string()
+    }
+
+    /// Returns a line comparing the input and the output, e.g. `files: 12 in, 5 out; chunks: 40
+    /// in, 9 out`.
+    pub fn display_sizes(&self) -> String {
+        format!(
+            "files: {} in, {} out; chunks: {} in, {} out",
+            self.files, self.output_files, self.chunks, self.output_chunks
+        )
+    }
+
+    /// Returns a multi-line report with a per-file and per-rule breakdown.
+    pub fn display_detailed(&self) -> String {
+        Detailed(self).to_string()
+    }
+
+    pub(crate) fn record_elision(&mut self, reason: &ElisionReason, replacements: &[&Replacement]) {
+        self.elided_blocks += 1;
+        *self.elisions_by_reason.entry(reason.clone()).or_default() += 1;
+        let mut record = |rule: String| *self.elisions_by_rule.entry(rule).or_default() += 1;
+        match reason {
+            ElisionReason::Replacement => {
+                for replacement in replacements {
+                    record(format!("{reason} {replacement}"));
+                }
+            }
+            _ => record(reason.to_string()),
+        }
+    }
+
+    pub(crate) fn record_output(&mut self, file_diff: &FileDiff) {
+        self.output_files += 1;
+        self.output_chunks += file_diff.chunks.len();
+        self.output_context_lines += file_diff.total_context_lines();
+        let (removed, added) = file_diff.total_changed_lines();
+        self.output_removed_lines += removed;
+        self.output_added_lines += added;
+    }
+
+    /// Returns the rules responsible for more than `threshold_percent` of all elided blocks,
+    /// along with their share. A rule that eats most of a diff may be hiding real changes.
+    pub fn dominant_rules(&self, threshold_percent: f64) -> Vec<(&str, f64)> {
+        self.elisions_by_rule
+            .iter()
+            .map(|(rule, &count)| {
+                (
+                    rule.as_str(),
+                    100.0 * count as f64 / self.elided_blocks as f64,
+                )
+            })
+            .filter(|&(_, percent)| percent > threshold_percent)
+            .collect()
+    }
+}
+
+struct Summary<'a>(&'a Stats);
+
+impl<'a> fmt::Display for Summary<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats = self.0;
+        write!(
+            f,
+            "{}/{} blocks filtered across {}/{} files",
+            stats.elided_blocks,
+            stats.changed_blocks,
+            stats
+                .per_file
+                .iter()
+                .filter(|file| file.elided_blocks > 0)
+                .count(),
+            stats.files
+        )
+    }
+}
+
+struct Detailed<'a>(&'a Stats);
+
+impl<'a> fmt::Display for Detailed<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats = self.0;
+        writeln!(f, "{}", Summary(stats))?;
+
+        let width = stats
+            .per_file
+            .iter()
+            .map(|file| file.file_name.len())
+            .chain(Some("file".len()))
+            .max()
+            .unwrap_or_default();
+        writeln!(f)?;
+        writeln!(f, "{:width$}  changed  elided  kept", "file")?;
+        for file in &stats.per_file {
+            writeln!(
+                f,
+                "{:width$}  {:>7}  {:>6}  {}",
+                file.file_name,
+                file.changed_blocks,
+                file.elided_blocks,
+                if file.kept { "yes" } else { "no" }
+            )?;
+        }
+
+        if !stats.elisions_by_rule.is_empty() {
+            let width = stats
+                .elisions_by_rule
+                .keys()
+                .map(String::len)
+                .chain(Some("rule".len()))
+                .max()
+                .unwrap_or_default();
+            writeln!(f)?;
+            writeln!(f, "{:width$}  elided", "rule")?;
+            for (rule, count) in &stats.elisions_by_rule {
+                writeln!(f, "{rule:width$}  {count:>6}")?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "output: {} files, {} context lines, {} removed, {} added",
+            stats.output_files,
+            stats.output_context_lines,
+            stats.output_removed_lines,
+            stats.output_added_lines
+        )
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The result of reducing one `NAME.input.diff` in a fixture directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixtureOutcome {
+    Pass,
+    /// The output differs from `NAME.expected.diff`.
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// There is no `NAME.expected.diff`.
+    MissingExpected,
+}
+
+#[derive(Clone, Debug)]
+pub struct FixtureResult {
+    /// The fixture name, i.e. the input file name without `.input.diff`.
+    pub name: String,
+    pub outcome: FixtureOutcome,
+}
+
+impl FixtureResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == FixtureOutcome::Pass
+    }
+}
+
+/// Reduces every `NAME.input.diff` directly in `dir` with `config` and compares the result with
+/// `NAME.expected.diff` next to it, for regression testing with fixtures that are easy to add
+/// and update. The results are sorted by name.
+pub fn run_fixtures(dir: &Path, config: &ProcessConfig) -> io::Result<Vec<FixtureResult>> {
+    let mut inputs = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    inputs.sort();
+    let mut results = Vec::new();
+    for input in inputs {
+        let Some(name) = input
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_suffix(".input.diff"))
+        else {
+            continue;
+        };
+        let actual = PatchSet::from(process_file_diffs(
+            parse_file_diffs(&fs::read_to_string(&input)?),
+            config,
+        ))
+        .to_string();
+        let outcome = match fs::read_to_string(expected_path(dir, name)) {
+            Ok(expected) if expected == actual => FixtureOutcome::Pass,
+            Ok(expected) => FixtureOutcome::Fail { expected, actual },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                FixtureOutcome::MissingExpected
+            }
+            Err(error) => return Err(error),
+        };
+        results.push(FixtureResult {
+            name: name.to_owned(),
+            outcome,
+        });
+    }
+    Ok(results)
+}
+
+/// Returns where the expected output of fixture `name` in `dir` is read from.
+pub fn expected_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.expected.diff"))
+}
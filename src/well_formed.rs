@@ -0,0 +1,126 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{parse_chunk_header, ChunkHeader, DiffParseError, FileDiff};
+use std::error::Error;
+use std::fmt;
+
+/// A structural problem found by `FileDiff::well_formed`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub file_name: String,
+    /// The start line of the offending chunk in the original file, or 0 if its header cannot be
+    /// parsed.
+    pub line: u32,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    MalformedHeader(DiffParseError),
+    /// The chunk starts before the one preceding it.
+    OutOfOrder,
+    /// The chunk starts within the lines covered by the one preceding it.
+    Overlapping,
+    /// The header's old count differs from the number of context and removed lines.
+    OldCountMismatch {
+        header: u32,
+        actual: u32,
+    },
+    /// The header's new count differs from the number of context and added lines.
+    NewCountMismatch {
+        header: u32,
+        actual: u32,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.file_name, self.line)?;
+        match &self.kind {
+            ValidationErrorKind::MalformedHeader(error) => write!(f, "{error}"),
+            ValidationErrorKind::OutOfOrder => write!(f, "chunk starts before the previous one"),
+            ValidationErrorKind::Overlapping => write!(f, "chunk overlaps the previous one"),
+            ValidationErrorKind::OldCountMismatch { header, actual } => write!(
+                f,
+                "header says {header} old lines, but the chunk has {actual}"
+            ),
+            ValidationErrorKind::NewCountMismatch { header, actual } => write!(
+                f,
+                "header says {header} new lines, but the chunk has {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+impl FileDiff<'_> {
+    /// Checks that the chunks are in order, do not overlap and have as many lines as their
+    /// headers say, returning every violation found. Elided blocks count as the changes they
+    /// were, while notes do not count, so a reduced diff is checked against the original headers.
+    /// This only makes sense before the headers are recomputed, e.g. by `context_lines`.
+    pub fn well_formed(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let error = |line, kind| ValidationError {
+            file_name: self.file_name().to_owned(),
+            line,
+            kind,
+        };
+        let mut previous: Option<ChunkHeader> = None;
+        for chunk in &self.chunks {
+            let header = match parse_chunk_header(&chunk.header) {
+                Ok(header) => header,
+                Err(parse_error) => {
+                    errors.push(error(0, ValidationErrorKind::MalformedHeader(parse_error)));
+                    continue;
+                }
+            };
+            if let Some(previous) = &previous {
+                if header.old_start < previous.old_start {
+                    errors.push(error(header.old_start, ValidationErrorKind::OutOfOrder));
+                } else if previous.old_start + previous.old_count > header.old_start {
+                    errors.push(error(header.old_start, ValidationErrorKind::Overlapping));
+                }
+            }
+
+            let old = chunk.old_side_line_count() as u32;
+            let new = chunk.new_side_line_count() as u32;
+            if old != header.old_count {
+                errors.push(error(
+                    header.old_start,
+                    ValidationErrorKind::OldCountMismatch {
+                        header: header.old_count,
+                        actual: old,
+                    },
+                ));
+            }
+            if new != header.new_count {
+                errors.push(error(
+                    header.old_start,
+                    ValidationErrorKind::NewCountMismatch {
+                        header: header.new_count,
+                        actual: new,
+                    },
+                ));
+            }
+            previous = Some(header);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+use std::io::{self, Write};
+
+/// Lines that may complete enough of a file header to be recognized as one. Anything else cannot
+/// end the file before it.
+const FILE_HEADER_LINES: &[&[u8]] = &[b"diff --git ", b"+++ "];
+
+/// Reduces a diff as it is written, forwarding each file to `inner` as soon as the next one
+/// starts, so a long diff can be piped through without holding all of it in memory.
+///
+/// The last file is only known to be complete at the end; call `finish` to write it and get
+/// `inner` back. If the writer is dropped instead, it is written on a best-effort basis and any
+/// error is ignored.
+pub struct DiffWriter<'c, W: Write> {
+    config: &'c ProcessConfig,
+    inner: Option<W>,
+    buffer: Vec<u8>,
+    /// How much of `buffer` was already checked for the start of a file.
+    scanned: usize,
+}
+
+impl<'c, W: Write> DiffWriter<'c, W> {
+    pub fn new(config: &'c ProcessConfig, inner: W) -> Self {
+        DiffWriter {
+            config,
+            inner: Some(inner),
+            buffer: Vec::new(),
+            scanned: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Reduces and writes whatever is left, returning the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_reduced(self.buffer.len())?;
+        self.buffer.clear();
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    /// Writes every file that is known to be complete, i.e. followed by the start of another.
+    fn write_complete_files(&mut self) -> io::Result<()> {
+        let Some(end) = self.buffer.iter().rposition(|&byte| byte == b'\n') else {
+            return Ok(());
+        };
+        let end = end + 1;
+        // Parsing is only worth it if a new file may have started since the last time.
+        let may_start_file = self.buffer[self.scanned..end]
+            .split(|&byte| byte == b'\n')
+            .any(|line| {
+                FILE_HEADER_LINES
+                    .iter()
+                    .any(|start| line.starts_with(start))
+            });
+        self.scanned = end;
+        if !may_start_file {
+            return Ok(());
+        }
+        let text = decode(&self.buffer[..end])?;
+        let file_diffs = parse_file_diffs(text);
+        let [_, .., last] = &file_diffs[..] else {
+            return Ok(());
+        };
+        let split = last.header.as_ptr() as usize - text.as_ptr() as usize;
+        self.write_reduced(split)?;
+        self.buffer.drain(..split);
+        self.scanned -= split;
+        Ok(())
+    }
+
+    /// Reduces the first `len` bytes of `buffer` and writes the result to `inner`.
+    fn write_reduced(&mut self, len: usize) -> io::Result<()> {
+        let text = decode(&self.buffer[..len])?;
+        let file_diffs = process_file_diffs(parse_file_diffs(text), self.config);
+        let output = PatchSet::from(file_diffs).to_string();
+        self.inner.as_mut().unwrap().write_all(output.as_bytes())
+    }
+}
+
+fn decode(bytes: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+impl<W: Write> Write for DiffWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        self.write_complete_files()?;
+        Ok(buf.len())
+    }
+
+    /// Only flushes `inner`; a file is not written until the next one starts or `finish` is
+    /// called.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for DiffWriter<'_, W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() && !self.buffer.is_empty() {
+            let _ = self.write_reduced(self.buffer.len());
+            let _ = self.inner.as_mut().unwrap().flush();
+        }
+    }
+}
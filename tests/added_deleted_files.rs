@@ -0,0 +1,83 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, ProcessConfig};
+
+const NEW_FILE: &str = "\
+diff --git a/new.cc b/new.cc
+new file mode 100644
+index 0000000..3b18e51
+--- /dev/null
++++ b/new.cc
+@@ -0,0 +1,3 @@
++namespace foo {
++  NOTREACHED();
++}  // namespace foo
+";
+
+const DELETED_FILE: &str = "\
+diff --git a/old.cc b/old.cc
+deleted file mode 100644
+index 3b18e51..0000000
+--- a/old.cc
++++ /dev/null
+@@ -1,2 +0,0 @@
+-  NOTREACHED_NORETURN();
+-int x;
+";
+
+const EMPTY_NEW_FILE: &str = "\
+diff --git a/empty.txt b/empty.txt
+new file mode 100644
+index 0000000..e69de29
+";
+
+#[test]
+fn added_and_deleted_files_round_trip() {
+    let input = [NEW_FILE, DELETED_FILE, EMPTY_NEW_FILE].concat();
+    let file_diffs = parse_file_diffs(&input);
+    assert_eq!(
+        file_diffs
+            .iter()
+            .map(|file_diff| (
+                file_diff.file_name(),
+                file_diff.is_new_file(),
+                file_diff.is_deleted_file()
+            ))
+            .collect::<Vec<_>>(),
+        [
+            ("new.cc", true, false),
+            ("old.cc", false, true),
+            ("empty.txt", true, false),
+        ]
+    );
+
+    for config in [
+        ProcessConfig::default(),
+        ProcessConfig {
+            context_lines: Some(3),
+            ..ProcessConfig::default()
+        },
+        ProcessConfig {
+            elide_scope_wrappers: true,
+            ..ProcessConfig::default()
+        },
+    ] {
+        let output = process_file_diffs(parse_file_diffs(&input), &config)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(output, [NEW_FILE, DELETED_FILE, EMPTY_NEW_FILE]);
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+const INPUT: &str = concat!(
+    "--- a/foo.cc\n",
+    "+++ b/foo.cc\n",
+    "@@ -1,6 +1,6 @@\n",
+    " void Foo() {\n",
+    "-  NOTREACHED_NORETURN();\n",
+    "+  NOTREACHED();\n",
+    " }\n",
+    " void Bar() {\n",
+    "-  int y = 2;\n",
+    "+  int y = 3;\n",
+    " }\n",
+);
+
+fn reduce(config: &ProcessConfig) -> String {
+    PatchSet::from(process_file_diffs(parse_file_diffs(INPUT), config)).to_string()
+}
+
+#[test]
+fn annotate_elisions() {
+    let config = ProcessConfig {
+        annotate_elisions: true,
+        ..ProcessConfig::default()
+    };
+    assert_eq!(
+        reduce(&config),
+        concat!(
+            "--- a/foo.cc\n",
+            "+++ b/foo.cc\n",
+            "@@ -1,7 +1,7 @@\n",
+            " void Foo() {\n",
+            " [diffreducer: elided]\n",
+            " }\n",
+            " void Bar() {\n",
+            "-  int y = 2;\n",
+            "+  int y = 3;\n",
+            " }\n",
+            "\n",
+        )
+    );
+}
+
+#[test]
+fn annotate_elision_reasons() {
+    let config = ProcessConfig {
+        annotate_elisions: true,
+        annotate_elision_reasons: true,
+        ..ProcessConfig::default()
+    };
+    let output = reduce(&config);
+    assert!(
+        output.contains("\n [diffreducer: elided (replacement)]\n"),
+        "{output}"
+    );
+}
@@ -0,0 +1,114 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const INPUT: &str = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,8 +1,8 @@
+ a
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ b
+ c
+ d
+ e
+ f
+-g
+\\ No newline at end of file
++h
+\\ No newline at end of file
+";
+
+fn reduce(input: &str, args: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn recomputes_headers_and_keeps_no_newline_markers() {
+    assert_eq!(
+        reduce(INPUT, &["--applicable"]),
+        "\
+--- a/a.cc
++++ b/a.cc
+@@ -5,4 +5,4 @@
+ d
+ e
+ f
+-g
+\\ No newline at end of file
++h
+\\ No newline at end of file
+
+"
+    );
+    assert!(reduce(INPUT, &["--applicable", "--context-lines", "0"]).contains("@@ -8,1 +8,1 @@\n"));
+}
+
+#[test]
+fn drops_chunks_that_become_pure_context() {
+    let input = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,2 +1,2 @@
+ a
+-  NOTREACHED_NORETURN();
+\\ No newline at end of file
++  NOTREACHED();
+\\ No newline at end of file
+";
+    assert_eq!(reduce(input, &["--applicable"]), "");
+}
+
+#[cfg(feature = "apply-check")]
+#[test]
+fn output_applies() {
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("diffreducer-applicable-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("a.cc"),
+        "a\n  NOTREACHED_NORETURN();\nb\nc\nd\ne\nf\ng",
+    )
+    .unwrap();
+    fs::write(dir.join("reduced.diff"), reduce(INPUT, &["--applicable"])).unwrap();
+    let status = Command::new("git")
+        .args(["apply", "reduced.diff"])
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(
+        fs::read_to_string(dir.join("a.cc")).unwrap(),
+        "a\n  NOTREACHED_NORETURN();\nb\nc\nd\ne\nf\nh"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
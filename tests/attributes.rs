@@ -0,0 +1,59 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+fn reduce(input: &str) -> String {
+    let config = ProcessConfig {
+        attribute_patterns: ProcessConfig::deprecated_attribute_patterns(),
+        ..ProcessConfig::default()
+    };
+    PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+}
+
+#[test]
+fn cc_deprecated_attribute() {
+    let output = reduce(include_str!("fixtures/deprecated_attribute_cc.diff"));
+    assert!(!output.contains("Use Display()"), "{output}");
+    // Added right before a changed declaration, so it has to stay.
+    assert!(output.contains("+  [[deprecated]]\n"), "{output}");
+}
+
+#[test]
+fn java_deprecated_attribute() {
+    let output = reduce(include_str!("fixtures/deprecated_attribute_java.diff"));
+    assert!(!output.contains("@Deprecated"), "{output}");
+    assert!(
+        output.contains("+  public int width() { return 1; }\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn rust_deprecated_attribute() {
+    let output = reduce(include_str!("fixtures/deprecated_attribute_rs.diff"));
+    assert!(!output.contains("use display"), "{output}");
+    assert!(output.contains("+    #[deprecated]\n"), "{output}");
+}
+
+#[test]
+fn attributes_are_kept_by_default() {
+    let input = include_str!("fixtures/deprecated_attribute_java.diff");
+    let output = PatchSet::from(process_file_diffs(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+    ))
+    .to_string();
+    assert!(output.contains("+  @Deprecated\n"), "{output}");
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+fn reduce(input: &str) -> String {
+    PatchSet::from(process_file_diffs(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+    ))
+    .to_string()
+}
+
+#[test]
+fn binary_patch_is_passed_through() {
+    let input = include_str!("fixtures/mixed_binary.diff");
+    let file_diffs = parse_file_diffs(input);
+    let file_names = file_diffs
+        .iter()
+        .map(|file_diff| file_diff.file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        file_names,
+        ["mixed/code.cc", "mixed/icon.png", "mixed/notes.txt"]
+    );
+    assert!(file_diffs[1].chunks.is_empty());
+
+    let output = reduce(input);
+    let start = input.find("diff --git a/mixed/icon.png").unwrap();
+    let end = input.find("diff --git a/mixed/notes.txt").unwrap();
+    assert!(output.contains(&input[start..end]), "{output}");
+    assert!(!output.contains("NOTREACHED"), "{output}");
+    assert!(output.contains("+gamma\n"), "{output}");
+}
+
+#[cfg(feature = "apply-check")]
+#[test]
+fn reduced_mixed_patch_applies() {
+    use std::fs;
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join(format!("diffreducer-apply-{}", std::process::id()));
+    fs::create_dir_all(dir.join("mixed")).unwrap();
+    let code = (1..=40)
+        .map(|i| match i {
+            5 => "  NOTREACHED_NORETURN();\n".to_owned(),
+            _ => format!("line {i}\n"),
+        })
+        .collect::<String>();
+    fs::write(dir.join("mixed/code.cc"), code).unwrap();
+    fs::write(dir.join("mixed/notes.txt"), "alpha\nbeta\n").unwrap();
+    fs::write(
+        dir.join("reduced.diff"),
+        reduce(include_str!("fixtures/mixed_binary.diff")),
+    )
+    .unwrap();
+
+    let status = Command::new("git")
+        .args(["apply", "--check", "reduced.diff"])
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,62 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{canonicalize, parse_file_diffs, PatchSet};
+
+#[test]
+fn canonical_form() {
+    let input = concat!(
+        "diff --git a/z.cc b/z.cc\n",
+        "index 1111111..2222222 100644\n",
+        "--- a/z.cc\n",
+        "+++ b/z.cc\n",
+        "@@ -1,9 +1,9 @@\n",
+        " 1\n 2\n 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n",
+        "diff --git a/a.cc b/a.cc\n",
+        "index 3333333..4444444\n",
+        "--- a/a.cc\n",
+        "+++ b/a.cc\n",
+        "@@ -10,2 +10,3 @@\n",
+        " x\n",
+        "+y\n",
+        " z\n",
+    );
+    let output = PatchSet::from(canonicalize(parse_file_diffs(input))).to_string();
+    assert_eq!(
+        output,
+        concat!(
+            // Sorted by path, without `index` lines.
+            "diff --git a/a.cc b/a.cc\n",
+            "--- a/a.cc\n",
+            "+++ b/a.cc\n",
+            "@@ -10,2 +10,3 @@\n",
+            " x\n",
+            "+y\n",
+            " z\n",
+            "\n",
+            "diff --git a/z.cc b/z.cc\n",
+            "--- a/z.cc\n",
+            "+++ b/z.cc\n",
+            // Context is trimmed to 3 lines, and the header recomputed to match.
+            "@@ -3,7 +3,7 @@\n",
+            " 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n",
+            "\n",
+        )
+    );
+    // Canonicalizing again changes nothing.
+    assert_eq!(
+        PatchSet::from(canonicalize(parse_file_diffs(&output))).to_string(),
+        output
+    );
+}
@@ -0,0 +1,50 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::heuristics::{CaseFolder, HeuristicPipeline, Normalizer};
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+#[test]
+fn folds_only_listed_tokens() {
+    let folder = CaseFolder::new(&["true", "NULL"]);
+    assert_eq!(
+        folder.normalize("if (x == TRUE && y != Null) IsTrue(True);"),
+        "if (x == true && y != null) IsTrue(true);"
+    );
+    assert_eq!(CaseFolder::new::<&str>(&[]).normalize("TRUE"), "TRUE");
+}
+
+#[test]
+fn mixed_case_tokens_are_elided() {
+    let mut pipeline = HeuristicPipeline::default();
+    pipeline.push(Box::new(CaseFolder::new(&["true", "null"])));
+    let config = ProcessConfig::builder().pipeline(pipeline).build().unwrap();
+    let input = include_str!("fixtures/ignore_case.diff");
+    let output = PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string();
+    assert!(!output.contains("SetEnabled"), "{output}");
+    assert!(!output.contains("Null"), "{output}");
+    assert!(!output.contains("UseTrueColor"), "{output}");
+    // `Foo` is not one of the tokens.
+    assert!(
+        output.contains("-  ResetFoo();\n+  Resetfoo();\n"),
+        "{output}"
+    );
+
+    let output = PatchSet::from(process_file_diffs(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+    ))
+    .to_string();
+    assert!(output.contains("+  SetEnabled(true);\n"), "{output}");
+}
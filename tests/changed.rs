@@ -0,0 +1,130 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{Changed, OwnedChanged, Replacement};
+
+#[test]
+fn single_token_change() {
+    let changed = |removed, added| Changed { removed, added };
+    assert!(changed(vec!["  OldName"], vec!["NewName  "]).is_single_token_change());
+    assert!(changed(vec!["", "OldName", ""], vec!["NewName"]).is_single_token_change());
+    assert!(!changed(vec!["Old(name)"], vec!["New (name)"]).is_single_token_change());
+    assert!(!changed(vec!["OldName"], vec![]).is_single_token_change());
+    assert!(!changed(vec!["a", "b"], vec!["c"]).is_single_token_change());
+}
+
+#[test]
+fn pure_additions_and_deletions() {
+    let changed = |removed, added| Changed { removed, added };
+    assert!(changed(vec![], vec!["a"]).is_pure_addition());
+    assert!(!changed(vec![], vec!["a"]).is_pure_deletion());
+    assert!(changed(vec!["a"], vec![]).is_pure_deletion());
+    assert!(!changed(vec!["a"], vec!["b"]).is_pure_addition());
+    assert!(!changed(vec!["a"], vec!["b"]).is_pure_deletion());
+    assert!(!changed(vec!["a"], vec!["b"]).is_empty());
+    let empty = changed(vec![], vec![]);
+    assert!(empty.is_empty());
+    assert!(!empty.is_pure_addition() && !empty.is_pure_deletion());
+}
+
+#[test]
+fn comment_reflow() {
+    let changed = |removed, added| Changed { removed, added };
+    assert!(changed(
+        vec!["  // The quick brown fox jumps over", "  // the lazy dog."],
+        vec!["  // The quick brown fox", "  // jumps over the lazy dog."],
+    )
+    .is_comment_reflow());
+    assert!(changed(
+        vec!["/* The quick brown fox */"],
+        vec!["/*", " * The quick", " * brown fox", " */"],
+    )
+    .is_comment_reflow());
+    assert!(changed(vec!["# a b"], vec!["#  a", "# b"]).is_comment_reflow());
+    // The words changed.
+    assert!(!changed(vec!["// a b"], vec!["// a c"]).is_comment_reflow());
+    // Not everything is a comment.
+    assert!(!changed(vec!["// a", "b"], vec!["// a b"]).is_comment_reflow());
+    assert!(!changed(vec!["// a"], vec![]).is_comment_reflow());
+}
+
+#[test]
+fn levenshtein_distance() {
+    let changed = |removed, added| Changed { removed, added };
+    assert_eq!(
+        changed(vec!["a", "b"], vec!["a", "b"]).levenshtein_distance(),
+        0
+    );
+    assert_eq!(
+        changed(vec!["a", "b", "c"], vec!["a", "c"]).levenshtein_distance(),
+        1
+    );
+    assert_eq!(
+        changed(vec!["a", "b"], vec!["a", "B", "c"]).levenshtein_distance(),
+        2
+    );
+    assert_eq!(changed(vec![], vec!["a", "b"]).levenshtein_distance(), 2);
+    // Lines are atomic, so a one-character edit still costs a whole line.
+    assert_eq!(
+        changed(vec!["int x;"], vec!["int y;"]).levenshtein_distance(),
+        1
+    );
+}
+
+#[test]
+fn from_lines() {
+    let owned = Changed::from_lines(vec!["a".to_owned()], vec!["b".to_owned(), "c".to_owned()]);
+    assert_eq!(owned.to_string(), "-a\n+b\n+c\n");
+    assert_eq!(owned.as_changed().levenshtein_distance(), 2);
+    assert_eq!(
+        OwnedChanged::from(Changed {
+            removed: vec!["a"],
+            added: vec!["b", "c"],
+        }),
+        owned
+    );
+    let input = String::from("a\nb\nc");
+    let lines = input.lines().collect::<Vec<_>>();
+    let changed = Changed {
+        removed: lines[..1].to_vec(),
+        added: lines[1..].to_vec(),
+    };
+    let into_owned = changed.into_owned();
+    drop(input);
+    assert_eq!(into_owned, owned);
+}
+
+#[test]
+fn try_apply_single_replacement() {
+    let changed = Changed {
+        removed: vec!["  NOTREACHED_NORETURN();", "  return;"],
+        added: vec!["  NOTREACHED();", "  return;"],
+    };
+    let replacement = Replacement::new("NOTREACHED_NORETURN", "NOTREACHED");
+    assert_eq!(
+        changed.try_apply_single_replacement(&replacement),
+        Some(changed.clone().into_owned())
+    );
+    // The replacement has to match, and account for the whole change.
+    let other = Replacement::new("DCHECK", "CHECK");
+    assert_eq!(changed.try_apply_single_replacement(&other), None);
+    let partial = Changed {
+        added: vec!["  NOTREACHED();", "  return 0;"],
+        ..changed.clone()
+    };
+    assert_eq!(partial.try_apply_single_replacement(&replacement), None);
+
+    let regex = Replacement::regex(r"(\w+)_NORETURN", "$1").unwrap();
+    assert!(changed.try_apply_single_replacement(&regex).is_some());
+}
@@ -0,0 +1,92 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_chunk_header, parse_file_diffs, ChunkHeader};
+
+#[test]
+fn parse() {
+    assert_eq!(
+        parse_chunk_header("@@ -27,8 +28 @@ Foo::Bar(\n"),
+        Ok(ChunkHeader {
+            old_start: 27,
+            old_count: 8,
+            new_start: 28,
+            new_count: 1,
+            annotation: Some("Foo::Bar(".to_owned()),
+        })
+    );
+    assert_eq!(
+        parse_chunk_header("@@ -0,0 +1,3 @@"),
+        Ok(ChunkHeader {
+            old_start: 0,
+            old_count: 0,
+            new_start: 1,
+            new_count: 3,
+            annotation: None,
+        })
+    );
+}
+
+#[test]
+fn parse_error() {
+    let error = parse_chunk_header("@@ -1,2 +1,2\n").unwrap_err();
+    assert_eq!(error.line, "@@ -1,2 +1,2");
+    assert!(parse_chunk_header("@@ -99999999999,1 +1,1 @@").is_err());
+    assert!(parse_chunk_header(" context").is_err());
+}
+
+#[test]
+fn round_trip() {
+    for line in [
+        "@@ -27,8 +28,9 @@ Foo::Bar(",
+        "@@ -0,0 +1,3 @@",
+        "@@ -5 +5 @@ int main() {",
+    ] {
+        let header = parse_chunk_header(line).unwrap();
+        assert_eq!(parse_chunk_header(&header.to_string()), Ok(header));
+    }
+    assert_eq!(
+        parse_chunk_header("@@ -5 +5 @@ int main() {")
+            .unwrap()
+            .to_string(),
+        "@@ -5,1 +5,1 @@ int main() {"
+    );
+}
+
+#[test]
+fn update_from_blocks() {
+    let input = concat!(
+        "--- a/foo.cc\n",
+        "+++ b/foo.cc\n",
+        "@@ -10,9 +10,9 @@ Foo()\n",
+        " a\n",
+        "-b\n",
+        "+c\n",
+        "+d\n",
+        " e\n",
+    );
+    let file_diffs = parse_file_diffs(input);
+    let chunk = &file_diffs[0].chunks[0];
+    let header = parse_chunk_header(&chunk.header).unwrap();
+    assert_eq!(
+        header.update_from_blocks(&chunk.blocks),
+        ChunkHeader {
+            old_start: 10,
+            old_count: 3,
+            new_start: 10,
+            new_count: 4,
+            annotation: Some("Foo()".to_owned()),
+        }
+    );
+}
@@ -0,0 +1,111 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+use std::num::NonZeroUsize;
+
+const MULTIPLE_HUNKS: &str = include_str!("fixtures/multiple_hunks.diff");
+
+fn reduce(input: &str, context_lines: usize) -> String {
+    let config = ProcessConfig {
+        context_lines: Some(context_lines),
+        ..ProcessConfig::default()
+    };
+    PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+}
+
+#[test]
+fn zero_context_lines() {
+    assert_eq!(
+        reduce(MULTIPLE_HUNKS, 0),
+        "\
+diff --git a/b.txt b/b.txt
+index c907da7..03dd036 100644
+--- a/b.txt
++++ b/b.txt
+@@ -6,0 +7,2 @@ line 1
++added A
++added B
+@@ -19,1 +20,0 @@ line 15
+-line 19
+@@ -40,1 +41,1 @@ line 36
+-line 40
++changed 40
+
+"
+    );
+}
+
+#[test]
+fn elided_changes_become_context() {
+    assert_eq!(
+        reduce(MULTIPLE_HUNKS, 2),
+        "\
+diff --git a/b.txt b/b.txt
+index c907da7..03dd036 100644
+--- a/b.txt
++++ b/b.txt
+@@ -5,4 +5,6 @@ line 1
+   NOTREACHED_NORETURN();
+ line 6
++added A
++added B
+ line 7
+ line 8
+@@ -17,5 +19,4 @@ line 15
+ line 17
+ line 18
+-line 19
+ line 20
+ line 21
+@@ -38,5 +39,5 @@ line 36
+ line 38
+ line 39
+-line 40
++changed 40
+ line 41
+ line 42
+
+"
+    );
+}
+
+#[test]
+fn keep_first_notes_omitted_hunks() {
+    let config = ProcessConfig {
+        context_lines: Some(1),
+        keep_first: NonZeroUsize::new(1),
+        ..ProcessConfig::default()
+    };
+    assert_eq!(
+        PatchSet::from(process_file_diffs(
+            parse_file_diffs(MULTIPLE_HUNKS),
+            &config
+        ))
+        .to_string(),
+        "\
+diff --git a/b.txt b/b.txt
+index c907da7..03dd036 100644
+--- a/b.txt
++++ b/b.txt
+@@ -6,3 +6,5 @@ line 1
+ line 6
++added A
++added B
+ line 7
+ [diffreducer: 2 more hunks omitted]
+
+"
+    );
+}
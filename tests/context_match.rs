@@ -0,0 +1,86 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    parse_file_diffs, process_file_diffs_with_stats, ContextMismatch, PatchSet, ProcessConfig,
+    Stats,
+};
+use std::fs;
+
+const INPUT: &str = "\
+--- a/a.cc
++++ b/a.cc
+@@ -2,3 +2,3 @@
+ int x;
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ int y;
+";
+
+#[test]
+fn context_mismatches() {
+    let file_diffs = parse_file_diffs(INPUT);
+    assert_eq!(
+        file_diffs[0].context_mismatches("// a.cc\nint x;\nfoo\nint y;\n"),
+        []
+    );
+    assert_eq!(
+        file_diffs[0].context_mismatches("// a.cc\nint x;\nfoo\nint z;\n"),
+        [ContextMismatch {
+            file_name: "a.cc".to_owned(),
+            line: 4,
+            expected: "int y;".to_owned(),
+            found: Some("int z;".to_owned()),
+        }]
+    );
+    let mismatches = file_diffs[0].context_mismatches("// a.cc\n");
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(
+        mismatches[0].to_string(),
+        "a.cc:2: context line \"int x;\" does not match the end of the file"
+    );
+}
+
+#[test]
+fn mismatched_files_are_passed_through() {
+    let dir = std::env::temp_dir().join(format!("diffreducer-context-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let reduce = |original: &str| {
+        fs::write(dir.join("a.cc"), original).unwrap();
+        let config = ProcessConfig::builder()
+            .reference_dir(Some(dir.clone()))
+            .require_context_match(true)
+            .build()
+            .unwrap();
+        let mut stats = Stats::default();
+        let output = PatchSet::from(process_file_diffs_with_stats(
+            parse_file_diffs(INPUT),
+            &config,
+            &mut stats,
+        ))
+        .to_string();
+        (output, stats.context_mismatches)
+    };
+
+    let (output, mismatches) = reduce("// a.cc\nint x;\nfoo\nint y;\n");
+    assert_eq!(output, "");
+    assert_eq!(mismatches, []);
+
+    let (output, mismatches) = reduce("// a.cc\nint w;\nfoo\nint y;\n");
+    assert!(output.contains("+  NOTREACHED();\n"), "{output}");
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].line, 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,140 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::similarity::SimilarityMetric;
+use diffreducer::{decide, Decision, ElisionReason, ProcessConfig};
+
+#[test]
+fn keeps_one_sided_blocks() {
+    let config = ProcessConfig::default();
+    assert_eq!(decide(&[], &["int x;"], &config), Decision::Keep);
+    assert_eq!(decide(&["int x;"], &[], &config), Decision::Keep);
+}
+
+#[test]
+fn elides_reflowed_call() {
+    let config = ProcessConfig::default();
+    assert_eq!(
+        decide(
+            &[
+                "  Foo(first_argument, second_argument,",
+                "      third_argument);"
+            ],
+            &[
+                "  Foo(",
+                "      first_argument, second_argument, third_argument);"
+            ],
+            &config,
+        ),
+        Decision::Elide(ElisionReason::Whitespace)
+    );
+}
+
+#[test]
+fn elides_reflowed_comment() {
+    let config = ProcessConfig::default();
+    assert_eq!(
+        decide(
+            &["  // The quick brown fox", "  // jumps over the lazy dog."],
+            &["  // The quick brown fox jumps over", "  // the lazy dog."],
+            &config,
+        ),
+        Decision::Elide(ElisionReason::Whitespace)
+    );
+}
+
+#[test]
+fn elides_replacement() {
+    let config = ProcessConfig::default();
+    assert_eq!(
+        decide(&["  NOTREACHED_NORETURN();"], &["  NOTREACHED();"], &config),
+        Decision::Elide(ElisionReason::Replacement)
+    );
+}
+
+#[test]
+fn keeps_real_change() {
+    let config = ProcessConfig::default();
+    assert_eq!(
+        decide(&["  int y = 2;"], &["  int y = 3;"], &config),
+        Decision::Keep
+    );
+}
+
+#[test]
+fn keeps_blocks_with_huge_lines() {
+    let config = ProcessConfig::default();
+    let removed = format!("var x = [{}NOTREACHED_NORETURN()];", "0, ".repeat(1 << 20));
+    let added = removed.replace("NOTREACHED_NORETURN", "NOTREACHED");
+    assert_eq!(decide(&[&removed], &[&added], &config), Decision::Keep);
+
+    let config = ProcessConfig {
+        max_line_length: usize::MAX,
+        ..ProcessConfig::default()
+    };
+    assert_eq!(
+        decide(&[&removed], &[&added], &config),
+        Decision::Elide(ElisionReason::Replacement)
+    );
+}
+
+#[test]
+fn elision_reasons_round_trip() {
+    for reason in ElisionReason::ALL {
+        assert_eq!(reason.to_string().parse(), Ok(reason.clone()));
+    }
+    assert!("whitespace".parse::<ElisionReason>().is_err());
+}
+
+#[test]
+fn elides_similar_blocks_with_metric() {
+    let removed = ["  Foo(alpha, beta, gamma, delta, epsilon, zeta, eta, theta, iota, kappa);"];
+    let added = ["  Foo(alpha, beta, gamma, delta, epsilon, zeta, eta, theta, iota, lambda);"];
+    assert_eq!(
+        decide(&removed, &added, &ProcessConfig::default()),
+        Decision::Keep
+    );
+    for similarity_metric in [
+        SimilarityMetric::Levenshtein,
+        SimilarityMetric::Jaccard,
+        SimilarityMetric::Lcs,
+    ] {
+        let config = ProcessConfig {
+            similarity_metric,
+            similarity_threshold: 0.8,
+            ..ProcessConfig::default()
+        };
+        assert_eq!(
+            decide(&removed, &added, &config),
+            Decision::Elide(ElisionReason::Similar),
+            "{similarity_metric}"
+        );
+    }
+}
+
+#[test]
+fn elides_similar_blocks_with_line_levenshtein() {
+    let removed = ["a();", "b();", "c();", "d();", "e();"];
+    let added = ["a();", "b();", "c();", "d();", "f(e);"];
+    let config = |similarity_threshold| ProcessConfig {
+        similarity_metric: SimilarityMetric::LineLevenshtein,
+        similarity_threshold,
+        ..ProcessConfig::default()
+    };
+    assert_eq!(
+        decide(&removed, &added, &config(0.8)),
+        Decision::Elide(ElisionReason::Similar)
+    );
+    assert_eq!(decide(&removed, &added, &config(0.9)), Decision::Keep);
+}
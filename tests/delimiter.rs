@@ -0,0 +1,47 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn diffreducer(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn each_delimited_diff_is_reduced() {
+    let a = "--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,2 @@\n x\n-  NOTREACHED_NORETURN();\n+  NOTREACHED();\n";
+    let b = "--- a/b.cc\n+++ b/b.cc\n@@ -1,2 +1,2 @@\n x\n-  int y = 1;\n+  int y = 2;\n";
+    let output = diffreducer(
+        &["--delimiter", "---8<---"],
+        &format!("{b}---8<---\n{a}---8<---\n{b}"),
+    );
+    // The first and last diffs are kept, the second is reduced to nothing, and the delimiter is
+    // echoed between them.
+    assert_eq!(output, format!("{b}\n---8<---\n---8<---\n{b}\n"));
+}
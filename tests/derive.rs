@@ -0,0 +1,101 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{derive_replacements, parse_file_diffs, process_file_diffs, ProcessConfig};
+
+const MIGRATION: &str = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,5 +1,5 @@
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ }
+-  NOTREACHED_NORETURN() << \"bad\";
+-  base::OldThing x;
++  NOTREACHED() << \"bad\";
++  base::NewThing x;
+--- a/b.cc
++++ b/b.cc
+@@ -1,4 +1,5 @@
+-    NOTREACHED_NORETURN();
++    NOTREACHED();
+ }
+-  int y = 1;
++  int y = 2;
++  int z;
+";
+
+#[test]
+fn repeated_substitutions() {
+    let replacements = derive_replacements(&parse_file_diffs(MIGRATION), 3);
+    let rules = replacements
+        .iter()
+        .map(|replacement| (&*replacement.before, &*replacement.after))
+        .collect::<Vec<_>>();
+    // `OldThing` -> `NewThing` is only seen once, and the block changing `y` also adds a line.
+    assert_eq!(rules, [("NOTREACHED_NORETURN", "NOTREACHED")]);
+
+    let rules = derive_replacements(&parse_file_diffs(MIGRATION), 1)
+        .into_iter()
+        .map(|replacement| (replacement.before, replacement.after))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rules,
+        [
+            ("NOTREACHED_NORETURN".into(), "NOTREACHED".into()),
+            ("OldThing".into(), "NewThing".into()),
+        ]
+    );
+}
+
+#[test]
+fn inconsistent_substitutions() {
+    let input = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,3 +1,3 @@
+-Foo();
+-Foo();
+-Foo();
++Bar();
++Bar();
++Baz();
+";
+    assert!(derive_replacements(&parse_file_diffs(input), 2).is_empty());
+}
+
+#[test]
+fn derived_rules_reduce_another_diff() {
+    let replacements = derive_replacements(&parse_file_diffs(MIGRATION), 3);
+    let config = ProcessConfig::builder()
+        .replacements(replacements)
+        .build()
+        .unwrap();
+    let input = "\
+--- a/c.cc
++++ b/c.cc
+@@ -1,3 +1,3 @@
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ }
+-  return 1;
++  return 2;
+";
+    let output = process_file_diffs(parse_file_diffs(input), &config)
+        .into_iter()
+        .map(|file_diff| file_diff.to_string())
+        .collect::<String>();
+    assert!(!output.contains("NOTREACHED"), "{output}");
+    assert!(output.contains("+  return 2;\n"), "{output}");
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::diff_summary::{FileSummary, Report};
+use diffreducer::{parse_file_diffs, process_file_diffs_with_stats, ProcessConfig, Stats};
+
+#[test]
+fn report() {
+    let input = concat!(
+        "--- a/foo.cc\n",
+        "+++ b/foo.cc\n",
+        "@@ -1,3 +1,4 @@\n",
+        " void Foo() {\n",
+        "-  NOTREACHED_NORETURN();\n",
+        "+  NOTREACHED();\n",
+        "@@ -10,3 +11,4 @@\n",
+        " void Bar() {\n",
+        "-  int y = 2;\n",
+        "+  int y = 3;\n",
+        "+  int z = 4;\n",
+        "--- a/bar.cc\n",
+        "+++ b/bar.cc\n",
+        "@@ -1 +1 @@\n",
+        "-  a(b);\n",
+        "+    a(b);\n",
+        "--- a/baz.cc\n",
+        "+++ b/baz.cc\n",
+        "@@ -1 +0,0 @@\n",
+        "-gone\n",
+    );
+    let mut stats = Stats::default();
+    let file_diffs = process_file_diffs_with_stats(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+        &mut stats,
+    );
+    let report = Report::new(&file_diffs, &stats);
+    assert_eq!(
+        report.files,
+        [
+            FileSummary {
+                file_path: "foo.cc".to_owned(),
+                added_lines: 2,
+                removed_lines: 1,
+                net_delta: 1,
+                chunks_retained: 1,
+                elided_blocks: 1,
+                matched_rules: vec!["NOTREACHED_NORETURN".to_owned()],
+            },
+            FileSummary {
+                file_path: "baz.cc".to_owned(),
+                added_lines: 0,
+                removed_lines: 1,
+                net_delta: -1,
+                chunks_retained: 1,
+                elided_blocks: 0,
+                matched_rules: Vec::new(),
+            },
+        ]
+    );
+    assert!(report.to_json().starts_with(concat!(
+        r#"{"files":[{"file_path":"foo.cc","added_lines":2,"removed_lines":1,"net_delta":1,"#,
+        r#""chunks_retained":1,"elided_blocks":1,"matched_rules":["NOTREACHED_NORETURN"]},"#
+    )));
+}
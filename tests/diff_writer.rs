@@ -0,0 +1,79 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, DiffWriter, PatchSet, ProcessConfig};
+use std::io::Write;
+
+const INPUT: &str = concat!(
+    "--- a/a.cc\n",
+    "+++ b/a.cc\n",
+    "@@ -1,2 +1,2 @@\n",
+    " x\n",
+    "-  NOTREACHED_NORETURN();\n",
+    "+  NOTREACHED();\n",
+    "diff --git a/b.cc b/b.cc\n",
+    "index 1234567..89abcde 100644\n",
+    "--- a/b.cc\n",
+    "+++ b/b.cc\n",
+    "@@ -1,2 +1,2 @@\n",
+    " x\n",
+    "-  int y = 1;\n",
+    "+  int y = 2;\n",
+    "--- a/c.cc\n",
+    "+++ b/c.cc\n",
+    "@@ -1 +1 @@\n",
+    "-  int z = 1;\n",
+    "+  int z = 2;\n",
+);
+
+#[test]
+fn same_output_as_processing_everything_at_once() {
+    let config = ProcessConfig::default();
+    let expected = PatchSet::from(process_file_diffs(parse_file_diffs(INPUT), &config)).to_string();
+    // Write a few bytes at a time, splitting lines and headers.
+    let mut writer = DiffWriter::new(&config, Vec::new());
+    for piece in INPUT.as_bytes().chunks(7) {
+        writer.write_all(piece).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(writer.finish().unwrap()).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn forwards_each_file_once_the_next_starts() {
+    let config = ProcessConfig::default();
+    let mut writer = DiffWriter::new(&config, Vec::new());
+    let b_start = INPUT.find("diff --git").unwrap();
+    let c_start = INPUT.find("--- a/c.cc").unwrap();
+    writer.write_all(&INPUT.as_bytes()[..b_start]).unwrap();
+    writer
+        .write_all(&INPUT.as_bytes()[b_start..c_start])
+        .unwrap();
+    // a.cc was reduced to nothing.
+    assert!(writer.get_ref().is_empty());
+    writer.write_all(b"--- a/c.cc\n+++ b/c.cc\n").unwrap();
+    let output = String::from_utf8(writer.get_ref().clone()).unwrap();
+    assert!(output.starts_with("diff --git a/b.cc b/b.cc\n"), "{output}");
+    assert!(!output.contains("c.cc"), "{output}");
+
+    // The last file is written when the writer is dropped.
+    let mut output = Vec::new();
+    DiffWriter::new(&config, &mut output)
+        .write_all(INPUT.as_bytes())
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with("+  int z = 2;\n\n"), "{output}");
+}
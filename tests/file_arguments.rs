@@ -0,0 +1,60 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn reduces_each_file_in_order() {
+    let dir = std::env::temp_dir().join(format!("diffreducer-files-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let a = dir.join("a.diff");
+    let b = dir.join("b.diff");
+    fs::write(
+        &a,
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,2 @@\n x\n-  int y = 1;\n+  int y = 2;\n",
+    )
+    .unwrap();
+    fs::write(
+        &b,
+        "--- a/b.cc\n+++ b/b.cc\n@@ -1,2 +1,2 @@\n x\n-  int z = 1;\n+  int z = 2;\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+        .args([&b, &a])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (b_start, a_start) = (
+        stdout.find("b/b.cc").unwrap(),
+        stdout.find("b/a.cc").unwrap(),
+    );
+    assert!(b_start < a_start, "{stdout}");
+
+    let missing = dir.join("missing.diff");
+    let output = Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+        .args([&a, &missing])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!("failed to read {}", missing.display())),
+        "{stderr}"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,74 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, FileFilter, GlobSet, ProcessConfig};
+
+#[test]
+fn globs() {
+    let globs = GlobSet::new(&["**/*.cc", "docs/?.md", "third_party/**"]);
+    assert!(globs.is_match("a.cc"));
+    assert!(globs.is_match("base/strings/a.cc"));
+    assert!(!globs.is_match("a.cc.orig"));
+    assert!(globs.is_match("docs/a.md"));
+    assert!(!globs.is_match("docs/ab.md"));
+    assert!(!globs.is_match("docs/x/a.md"));
+    assert!(globs.is_match("third_party/zlib/zlib.h"));
+    assert!(!globs.is_match("src/third_party/zlib.h"));
+    // Regex syntax is matched literally.
+    assert!(GlobSet::new(&["a+b.c"]).is_match("a+b.c"));
+    assert!(!GlobSet::new(&["a+b.c"]).is_match("aab.c"));
+}
+
+#[test]
+fn from_toml() {
+    let filter = FileFilter::from_toml(
+        "[file_filter]\ninclude = [\"**/*.cc\"]\nexclude = [\"third_party/**\"]\n",
+    )
+    .unwrap()
+    .unwrap();
+    assert!(filter.matches("base/a.cc"));
+    assert!(!filter.matches("base/a.h"));
+    assert!(!filter.matches("third_party/a.cc"));
+
+    let filter = FileFilter::from_toml("[file_filter]\nexclude = [\"*.h\"]\n")
+        .unwrap()
+        .unwrap();
+    assert!(filter.matches("a.cc"));
+    assert!(!filter.matches("a.h"));
+
+    assert!(FileFilter::from_toml("").unwrap().is_none());
+    assert!(FileFilter::from_toml("[file_filter]\ninclude = \"*.cc\"\n").is_err());
+    assert!(FileFilter::from_toml("[file_filter]\nincludes = []\n").is_err());
+    assert!(FileFilter::from_toml("[file_filter\n").is_err());
+}
+
+#[test]
+fn filtered_files_are_dropped() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+    );
+    let config = ProcessConfig::builder()
+        .file_filter(Some(FileFilter {
+            include: GlobSet::new(&["trunk/**"]),
+            exclude: GlobSet::new(&["**/*.c"]),
+        }))
+        .build()
+        .unwrap();
+    let file_names = process_file_diffs(parse_file_diffs(input), &config)
+        .iter()
+        .map(|file_diff| file_diff.file_name().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(file_names, ["trunk/src/widget.h"]);
+}
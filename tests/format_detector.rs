@@ -0,0 +1,65 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::format_detector::{detect_diff_format, DiffFormat};
+use diffreducer::{DiffParser, ParseErrorPolicy, ParserConfig};
+
+#[test]
+fn detect_formats() {
+    assert_eq!(
+        detect_diff_format(include_str!("fixtures/deprecated_attribute_rs.diff")),
+        DiffFormat::Git
+    );
+    assert_eq!(
+        detect_diff_format(include_str!("fixtures/svn.diff")),
+        DiffFormat::Svn
+    );
+    assert_eq!(
+        detect_diff_format("diff -r 1a2b3c4d foo.c\n--- a/foo.c\n+++ b/foo.c\n"),
+        DiffFormat::Mercurial
+    );
+    assert_eq!(
+        detect_diff_format("--- a.c\t2024-01-01\n+++ b.c\t2024-01-02\n@@ -1 +1 @@\n"),
+        DiffFormat::Plain
+    );
+    assert_eq!(detect_diff_format("2,3c\nfoo\n.\n"), DiffFormat::Ed);
+    assert_eq!(detect_diff_format("hello\n"), DiffFormat::Unknown);
+    assert_eq!(detect_diff_format(""), DiffFormat::Unknown);
+    let mixed = concat!(
+        include_str!("fixtures/deprecated_attribute_rs.diff"),
+        include_str!("fixtures/svn.diff")
+    );
+    assert_eq!(detect_diff_format(mixed), DiffFormat::Unknown);
+}
+
+#[test]
+fn parser_detects_format() {
+    let parser = DiffParser::with_config(ParserConfig {
+        error_policy: ParseErrorPolicy::Fail,
+        detect_format: true,
+        ..ParserConfig::default()
+    });
+    let error = parser.parse("1a\nfoo\n.\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 1 (byte 0): unsupported diff format (ed): \"1a\""
+    );
+    assert_eq!(
+        parser
+            .parse(include_str!("fixtures/svn.diff"))
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,137 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+fn reduce(input: &str) -> String {
+    PatchSet::from(process_file_diffs(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+    ))
+    .to_string()
+}
+
+#[test]
+fn svn_index_headers() {
+    let input = include_str!("fixtures/svn.diff");
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs
+            .iter()
+            .map(|file_diff| file_diff.file_name())
+            .collect::<Vec<_>>(),
+        ["trunk/src/widget.c", "trunk/src/widget.h"]
+    );
+
+    let output = reduce(input);
+    assert!(!output.contains("widget.c"), "{output}");
+    assert!(
+        output.starts_with(concat!(
+            "Index: trunk/src/widget.h\n",
+            "===================================================================\n",
+            "--- trunk/src/widget.h\t(revision 1234)\n",
+        )),
+        "{output}"
+    );
+    assert!(output.contains("+  int height;\n"), "{output}");
+}
+
+#[test]
+fn diffstat_preamble_is_skipped() {
+    let input = include_str!("fixtures/diffstat.diff");
+    assert_eq!(parse_file_diffs(input).len(), 3);
+    let output = reduce(input);
+    // The whitespace change in widget.h is elided, so the diffstat would be stale.
+    assert!(
+        output.starts_with("diff --git a/scope/gadget.cc b/scope/gadget.cc\n"),
+        "{output}"
+    );
+    assert!(!output.contains("files changed"), "{output}");
+}
+
+#[test]
+fn index_line_without_mode() {
+    let input = include_str!("fixtures/modeless_index.diff");
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs
+            .iter()
+            .map(|file_diff| file_diff.file_name())
+            .collect::<Vec<_>>(),
+        ["src/first.c", "src/second.c"]
+    );
+    assert!(file_diffs[1].header.starts_with(
+        "diff --git a/src/second.c b/src/second.c\nindex 1234567890ab..89abcdef0123\n"
+    ));
+    assert_eq!(
+        reduce(input),
+        format!("{input}\n").replace("}\ndiff", "}\n\ndiff")
+    );
+}
+
+#[test]
+fn tightly_concatenated_files() {
+    for input in [
+        include_str!("fixtures/concatenated_git.diff"),
+        include_str!("fixtures/concatenated_plain.diff"),
+        include_str!("fixtures/concatenated_diff_r.diff"),
+    ] {
+        let file_diffs = parse_file_diffs(input);
+        assert_eq!(
+            file_diffs
+                .iter()
+                .map(|file_diff| file_diff.file_name())
+                .collect::<Vec<_>>(),
+            ["one.txt", "two.txt"]
+        );
+        // Nothing from the second file's header ends up in the first file's last chunk.
+        assert_eq!(
+            file_diffs[0].chunks[0].to_string(),
+            "@@ -1,2 +1,2 @@\n a\n-b\n+B\n"
+        );
+        assert!(
+            file_diffs[1].chunks[0].to_string().starts_with("@@ -1,"),
+            "{input}"
+        );
+        assert_eq!(file_diffs[1].total_changed_lines(), (1, 1));
+    }
+}
+
+#[test]
+fn own_output_can_be_parsed_again() {
+    let input = include_str!("fixtures/concatenated_git.diff");
+    let output = reduce(input);
+    assert!(output.contains("+B\n\ndiff --git"), "{output}");
+    assert_eq!(reduce(&output), output);
+}
+
+#[test]
+fn diff_u_timestamps() {
+    let input = include_str!("fixtures/diff_u_timestamps.diff");
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs
+            .iter()
+            .map(|file_diff| (file_diff.file_name(), file_diff.chunks.len()))
+            .collect::<Vec<_>>(),
+        [("widget.c.new", 1), ("new/widget.h", 1)]
+    );
+
+    let output = reduce(input);
+    assert!(!output.contains("widget.c"), "{output}");
+    assert_eq!(
+        output,
+        format!("{}\n", &input[input.find("--- old/widget.h").unwrap()..])
+    );
+}
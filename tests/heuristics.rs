@@ -0,0 +1,130 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::heuristics::{
+    CaseFolder, CommentStripper, HeuristicPipeline, Normalizer, ParenFixer, WhitespaceNormalizer,
+};
+use diffreducer::{decide, Decision, ElisionReason, ProcessConfig};
+
+#[test]
+fn whitespace_normalizer() {
+    assert_eq!(
+        WhitespaceNormalizer.normalize("  foo(a,\n      b);"),
+        " foo(a, b);"
+    );
+    // A single space is left alone, and so is an empty string.
+    assert_eq!(WhitespaceNormalizer.normalize("a b"), "a b");
+    assert_eq!(WhitespaceNormalizer.normalize(""), "");
+}
+
+#[test]
+fn comment_stripper() {
+    let stripper = CommentStripper(vec!["// ".to_owned(), "# ".to_owned()]);
+    assert_eq!(
+        stripper.normalize("  // Does a thing.\n# Another\ncode(); // trailing"),
+        "Does a thing.\nAnother\ncode(); // trailing"
+    );
+    // Only the first matching delimiter is stripped.
+    assert_eq!(stripper.normalize("// // nested"), "// nested");
+    assert_eq!(CommentStripper(Vec::new()).normalize("  // x"), "// x");
+}
+
+#[test]
+fn case_folder() {
+    let folder = CaseFolder::new(&["true"]);
+    assert_eq!(
+        folder.normalize("TRUE || True || IsTrue"),
+        "true || true || IsTrue"
+    );
+    // Without tokens, nothing is folded.
+    let empty = CaseFolder::new::<&str>(&[]);
+    assert_eq!(empty.normalize("TRUE || Null"), "TRUE || Null");
+    assert_eq!(empty.name(), "case-folder");
+}
+
+#[test]
+fn paren_fixer() {
+    assert_eq!(ParenFixer.normalize("foo( a, ( b))"), "foo(a, (b))");
+    assert_eq!(ParenFixer.normalize("foo (a)"), "foo (a)");
+}
+
+#[test]
+fn default_pipeline_keeps_elision_behaviour() {
+    let pipeline = HeuristicPipeline::default();
+    assert_eq!(
+        pipeline
+            .normalizers()
+            .iter()
+            .map(|normalizer| normalizer.name())
+            .collect::<Vec<_>>(),
+        ["comment-stripper", "whitespace", "paren-fixer"]
+    );
+
+    let config = ProcessConfig::default();
+    // A reflowed comment and a reflowed call are elided as whitespace-only.
+    assert_eq!(
+        decide(
+            &["  // Returns the width of the", "  // widget."],
+            &["  // Returns the width of the widget."],
+            &config
+        ),
+        Decision::Elide(ElisionReason::Whitespace)
+    );
+    assert_eq!(
+        decide(
+            &["  Foo(a,", "      b);"],
+            &["  Foo(", "      a, b);"],
+            &config
+        ),
+        Decision::Elide(ElisionReason::Whitespace)
+    );
+    // Anything else is kept.
+    assert_eq!(
+        decide(&["  Foo(a, b);"], &["  Foo(a, c);"], &config),
+        Decision::Keep
+    );
+}
+
+#[test]
+fn debug_trace() {
+    let pipeline = HeuristicPipeline::default();
+    assert_eq!(
+        pipeline.debug_trace("  // Foo(\n  //     a);"),
+        concat!(
+            "input: \"  // Foo(\\n  //     a);\"\n",
+            "comment-stripper: \"Foo(\\n    a);\"\n",
+            "whitespace: \"Foo( a);\"\n",
+            "paren-fixer: \"Foo(a);\"\n",
+        )
+    );
+    assert_eq!(
+        HeuristicPipeline::empty().debug_trace("x"),
+        "input: \"x\"\n"
+    );
+}
+
+#[test]
+fn debug_lists_steps() {
+    let mut pipeline = HeuristicPipeline::default();
+    pipeline.push(Box::new(CaseFolder::new(&["true"])));
+    assert_eq!(
+        format!("{pipeline:?}"),
+        "HeuristicPipeline { steps: [\"comment-stripper\", \"whitespace\", \"paren-fixer\", \
+         \"case-folder\"] }"
+    );
+    assert_eq!(
+        format!("{:?}", HeuristicPipeline::empty()),
+        "HeuristicPipeline { steps: [] }"
+    );
+}
@@ -0,0 +1,131 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{diff_to_hunks, parse_file_diffs, process_hunk, ChunkBlock, ProcessConfig};
+
+#[test]
+fn one_hunk_per_change() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    let hunks = diff_to_hunks(&file_diffs[0], 1);
+    let headers = hunks
+        .iter()
+        .map(|hunk| hunk.header.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headers[..2],
+        ["@@ -4,3 +4,3 @@ line 1", "@@ -6,2 +6,4 @@ line 1"]
+    );
+    assert!(hunks.iter().all(|hunk| hunk.file_path == "b.txt"));
+    let ChunkBlock::Context(before) = &hunks[1].blocks[0] else {
+        panic!("{:?}", hunks[1].blocks);
+    };
+    assert_eq!(before, &["line 6"]);
+    assert_eq!(hunks[1].blocks.len(), 3);
+}
+
+#[test]
+fn without_context() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    let hunks = diff_to_hunks(&file_diffs[0], 0);
+    assert!(hunks.iter().all(|hunk| hunk.blocks.len() == 1));
+    // A pure addition is described by the line before it.
+    assert_eq!(hunks[1].header.to_string(), "@@ -6,0 +7,2 @@ line 1");
+}
+
+#[test]
+fn to_patch_string() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    let hunks = diff_to_hunks(&file_diffs[0], 1);
+    assert_eq!(
+        hunks[0].to_patch_string(),
+        concat!(
+            "--- a/b.txt\n",
+            "+++ b/b.txt\n",
+            "@@ -4,3 +4,3 @@ line 1\n",
+            " line 4\n",
+            "-  NOTREACHED_NORETURN();\n",
+            "+  NOTREACHED();\n",
+            " line 6\n",
+        )
+    );
+    for hunk in &hunks {
+        let reparsed = parse_file_diffs(&hunk.to_patch_string())
+            .into_iter()
+            .map(|file_diff| file_diff.to_string())
+            .collect::<String>();
+        assert_eq!(reparsed, hunk.to_patch_string());
+    }
+}
+
+#[cfg(feature = "apply-check")]
+#[test]
+fn each_hunk_applies() {
+    use std::fs;
+    use std::process::Command;
+
+    let dir = std::env::temp_dir().join(format!("diffreducer-hunks-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let original = (1..=45)
+        .map(|i| match i {
+            5 => "  NOTREACHED_NORETURN();\n".to_owned(),
+            _ => format!("line {i}\n"),
+        })
+        .collect::<String>();
+    fs::write(dir.join("b.txt"), original).unwrap();
+
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    for context in [0, 1, 3] {
+        for hunk in diff_to_hunks(&file_diffs[0], context) {
+            fs::write(dir.join("hunk.diff"), hunk.to_patch_string()).unwrap();
+            let status = Command::new("git")
+                .args(["apply", "--check", "--unidiff-zero", "hunk.diff"])
+                .current_dir(&dir)
+                .status()
+                .unwrap();
+            assert!(status.success(), "{}", hunk.to_patch_string());
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn process_hunks() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    let config = ProcessConfig::default();
+    let kept = diff_to_hunks(&file_diffs[0], 3)
+        .into_iter()
+        .filter_map(|hunk| process_hunk(hunk, &config))
+        .map(|hunk| hunk.header.to_string())
+        .collect::<Vec<_>>();
+    // The NOTREACHED change is mechanical.
+    assert_eq!(
+        kept,
+        [
+            "@@ -6,4 +6,6 @@ line 1",
+            "@@ -16,7 +18,6 @@ line 15",
+            "@@ -37,7 +38,7 @@ line 36"
+        ]
+    );
+
+    let input = "--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n x\n+#[deprecated]\n fn f() {}\n";
+    let config = ProcessConfig {
+        attribute_patterns: ProcessConfig::deprecated_attribute_patterns(),
+        ..ProcessConfig::default()
+    };
+    let hunks = diff_to_hunks(&parse_file_diffs(input)[0], 1);
+    assert!(process_hunk(hunks[0].clone(), &config).is_none());
+    // Without the declaration after it, the attribute cannot be judged.
+    let hunks = diff_to_hunks(&parse_file_diffs(input)[0], 0);
+    assert!(process_hunk(hunks[0].clone(), &config).is_some());
+}
@@ -0,0 +1,74 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::heuristics::HeuristicPipeline;
+use diffreducer::{parse_file_diffs, Changed, PatchSet};
+
+const INPUT: &str = concat!(
+    "--- a/foo.cc\n",
+    "+++ b/foo.cc\n",
+    "@@ -1,2 +1,2 @@ Foo()\n",
+    " \"quoted\"\ttab\n",
+    "-a\n",
+    "+b\n",
+    "--- a/bar.cc\n",
+    "+++ b/bar.cc\n",
+    "@@ -1 +1 @@\n",
+    "-a\n",
+    "+b\n",
+);
+
+#[test]
+fn json() {
+    let patch_set = PatchSet::from(parse_file_diffs(INPUT));
+    assert_eq!(
+        patch_set.to_json(None),
+        concat!(
+            r#"{"files":[{"file":"foo.cc","chunks":[{"header":"@@ -1,2 +1,2 @@ Foo()","blocks":["#,
+            r#"{"context":["\"quoted\"\ttab"]},{"removed":["a"],"added":["b"]}]}]},"#,
+            r#"{"file":"bar.cc","chunks":[{"header":"@@ -1 +1 @@","blocks":["#,
+            r#"{"removed":["a"],"added":["b"]}]}]}]}"#,
+            "\n"
+        )
+    );
+    assert_eq!(patch_set.to_ndjson(None).lines().count(), 2);
+}
+
+#[test]
+fn fingerprints() {
+    let pipeline = HeuristicPipeline::default();
+    let changed = |removed, added| Changed { removed, added };
+    let fingerprint = changed(vec!["foo(a, b);"], vec!["foo(a,", "    b);"]).fingerprint(&pipeline);
+    assert_eq!(
+        changed(vec!["foo(a,  b);"], vec!["foo(a, b);"]).fingerprint(&pipeline),
+        fingerprint
+    );
+    assert_ne!(
+        changed(vec!["foo(a, c);"], vec!["foo(a, b);"]).fingerprint(&pipeline),
+        fingerprint
+    );
+    // Stable across releases, so it can be stored.
+    assert_eq!(
+        changed(vec![], vec![]).fingerprint(&HeuristicPipeline::empty()),
+        0xa8c7f832281a39c5
+    );
+
+    let patch_set = PatchSet::from(parse_file_diffs(INPUT));
+    let ndjson = patch_set.to_ndjson(Some(&pipeline));
+    let fingerprints = ndjson
+        .lines()
+        .map(|line| line.split("\"fingerprint\":").nth(1).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(fingerprints[0], fingerprints[1]);
+}
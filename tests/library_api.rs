@@ -0,0 +1,60 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    parse_file_diffs, process_file_diffs, Changed, ChunkBlock, FileDiff, PatchSet, ProcessConfig,
+};
+use std::borrow::Cow;
+
+const INPUT: &str = concat!(
+    "--- a/foo.cc\n",
+    "+++ b/foo.cc\n",
+    "@@ -1,3 +1,3 @@\n",
+    " void Foo() {\n",
+    "-  NOTREACHED_NORETURN();\n",
+    "+  NOTREACHED();\n",
+    " }\n",
+    "@@ -10,3 +10,3 @@\n",
+    " void Bar() {\n",
+    "-  int y = 2;\n",
+    "+  int y = 3;\n",
+    " }\n",
+);
+
+#[test]
+fn parsed_diffs_borrow_the_input() {
+    let input = INPUT.to_owned();
+    let file_diffs = parse_file_diffs(&input);
+    assert!(matches!(file_diffs[0].header, Cow::Borrowed(_)));
+    let ChunkBlock::Changed(Changed { removed, .. }) = &file_diffs[0].chunks[1].blocks[1] else {
+        panic!("{:?}", file_diffs[0].chunks[1].blocks);
+    };
+    // The lines are slices of `input`, not copies.
+    let offset = removed[0].as_ptr() as usize - input.as_ptr() as usize;
+    assert_eq!(&input[offset..offset + removed[0].len()], "  int y = 2;");
+}
+
+#[test]
+fn process_without_the_binary() {
+    let file_diffs: Vec<FileDiff> =
+        process_file_diffs(parse_file_diffs(INPUT), &ProcessConfig::default());
+    assert_eq!(file_diffs.len(), 1);
+    assert_eq!(file_diffs[0].file_name(), "foo.cc");
+    let output = PatchSet::from(file_diffs).to_string();
+    assert!(!output.contains("NOTREACHED"), "{output}");
+    assert!(
+        output.contains("-  int y = 2;\n+  int y = 3;\n"),
+        "{output}"
+    );
+}
@@ -0,0 +1,90 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    parse_file_diffs, process_file_diffs, process_file_diffs_preserving_context, ChunkBlock,
+    ProcessConfig,
+};
+
+const INPUT: &str = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,4 +1,5 @@
+ a
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ b
+-c
++d
++e
+";
+
+#[test]
+fn block_line_counts() {
+    let file_diffs = parse_file_diffs(INPUT);
+    let chunk = &file_diffs[0].chunks[0];
+    assert_eq!(
+        chunk
+            .blocks
+            .iter()
+            .map(ChunkBlock::line_count)
+            .collect::<Vec<_>>(),
+        [1, 2, 1, 3]
+    );
+    assert_eq!(chunk.total_raw_lines(), 7);
+    assert_eq!(chunk.old_side_line_count(), 4);
+    assert_eq!(chunk.new_side_line_count(), 5);
+    assert_eq!(ChunkBlock::Note("[note]".to_owned()).line_count(), 0);
+
+    // Elided blocks still count their original lines.
+    let reduced = process_file_diffs(file_diffs.clone(), &ProcessConfig::default());
+    let chunk = &reduced[0].chunks[0];
+    assert!(matches!(chunk.blocks[1], ChunkBlock::Elided(..)));
+    assert_eq!(chunk.total_raw_lines(), 7);
+    assert_eq!(chunk.old_side_line_count(), 4);
+    assert_eq!(chunk.new_side_line_count(), 5);
+}
+
+#[test]
+fn block_accessors() {
+    let file_diffs = parse_file_diffs(INPUT);
+    let blocks = &file_diffs[0].chunks[0].blocks;
+    assert_eq!(blocks[0].as_context(), Some(&vec!["a"]));
+    assert!(blocks[0].as_changed().is_none());
+    assert_eq!(blocks[1].as_changed().map(|c| c.removed.len()), Some(1));
+    assert!(blocks[1].as_context().is_none());
+    assert_eq!(
+        blocks[3].as_changed().map(|c| c.added.clone()),
+        Some(vec!["d", "e"])
+    );
+}
+
+#[test]
+fn pure_context() {
+    let context_only = parse_file_diffs("--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,2 @@\n a\n b\n");
+    assert!(context_only[0].chunks[0].has_pure_context());
+    assert!(!context_only[0].chunks[0].has_changes());
+
+    let file_diffs = parse_file_diffs(INPUT);
+    assert!(file_diffs[0].chunks[0].has_changes());
+    assert!(!file_diffs[0].chunks[0].has_pure_context());
+
+    // Once every change is elided, only context is rendered.
+    let elided = parse_file_diffs(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,2 @@\n a\n-  NOTREACHED_NORETURN();\n+  NOTREACHED();\n",
+    );
+    let reduced = process_file_diffs_preserving_context(elided, &ProcessConfig::default());
+    assert!(reduced[0].0.chunks[0].has_pure_context());
+    assert_eq!(reduced[0].1, [false]);
+}
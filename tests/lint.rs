@@ -0,0 +1,88 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::lint::{lint_replacements, LintWarning, OverlapKind};
+use diffreducer::{ProcessConfig, Replacement};
+
+fn overlaps(replacements: &[Replacement]) -> Vec<(OverlapKind, String, String)> {
+    lint_replacements(replacements)
+        .into_iter()
+        .map(|LintWarning::OverlappingReplacement(warning)| {
+            (
+                warning.kind,
+                warning.first.before.into_owned(),
+                warning.second.before.into_owned(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn cascade() {
+    let replacements = [
+        Replacement::new("DCHECK_IS_ON", "CHECK_IS_ON"),
+        Replacement::new("CHECK_IS_ON", "EXPENSIVE_CHECKS"),
+    ];
+    assert_eq!(
+        overlaps(&replacements),
+        [(
+            OverlapKind::Cascade,
+            "DCHECK_IS_ON".to_owned(),
+            "CHECK_IS_ON".to_owned()
+        )]
+    );
+    assert_eq!(
+        lint_replacements(&replacements)[0].to_string(),
+        "replacement `DCHECK_IS_ON -> CHECK_IS_ON` produces \"CHECK_IS_ON\", which replacement \
+         `CHECK_IS_ON -> EXPENSIVE_CHECKS` rewrites again"
+    );
+}
+
+#[test]
+fn shadowing() {
+    let replacements = [
+        Replacement::new("NOTREACHED", "NOTREACHED_IN_MIGRATION"),
+        Replacement::new("NOTREACHED_NORETURN", "NOTREACHED"),
+    ];
+    // `NOTREACHED_IN_MIGRATION` does not contain `NOTREACHED_NORETURN`, so there is no cascade.
+    assert_eq!(
+        overlaps(&replacements),
+        [(
+            OverlapKind::Shadowing,
+            "NOTREACHED".to_owned(),
+            "NOTREACHED_NORETURN".to_owned()
+        )]
+    );
+    // The other way around, the longer one matches first, but its result is rewritten again.
+    let reversed = [replacements[1].clone(), replacements[0].clone()];
+    assert_eq!(
+        overlaps(&reversed),
+        [(
+            OverlapKind::Cascade,
+            "NOTREACHED_NORETURN".to_owned(),
+            "NOTREACHED".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn build_records_lint_warnings() {
+    let config = ProcessConfig::builder().build().unwrap();
+    assert!(config.lint_warnings.is_empty());
+    let config = ProcessConfig::builder()
+        .replacement(Replacement::new("NOTREACHED", "NOTREACHED_IN_MIGRATION"))
+        .build()
+        .unwrap();
+    assert_eq!(config.lint_warnings.len(), 1);
+}
@@ -0,0 +1,37 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+fn reduce(elide_net_noops: bool) -> String {
+    let config = ProcessConfig {
+        elide_net_noops,
+        ..ProcessConfig::default()
+    };
+    let input = include_str!("fixtures/net_noop.diff");
+    PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+}
+
+#[test]
+fn reverted_change_is_elided() {
+    let output = reduce(true);
+    assert!(!output.contains("SetTimeout"), "{output}");
+    assert!(output.contains("+value twenty\n"), "{output}");
+}
+
+#[test]
+fn reverted_change_is_kept_by_default() {
+    let output = reduce(false);
+    assert_eq!(output.matches("SetTimeout").count(), 4, "{output}");
+}
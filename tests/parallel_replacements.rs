@@ -0,0 +1,55 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{decide, Decision, ProcessConfig, Replacement};
+
+#[test]
+fn same_result_as_sequential() {
+    // The second replacement only applies after the first, and the third overlaps the first.
+    let replacements = vec![
+        Replacement::new("Foo", "Bar"),
+        Replacement::new("BarBaz", "Qux"),
+        Replacement::new("ooB", "xx"),
+    ];
+    let sequential = ProcessConfig::builder()
+        .replacements(replacements.clone())
+        .build()
+        .unwrap();
+    let parallel = ProcessConfig::builder()
+        .replacements(replacements)
+        .parallel_replacements(true)
+        .build()
+        .unwrap();
+    assert!(sequential.replacement_matcher.is_none());
+    assert!(parallel.replacement_matcher.is_some());
+
+    for (removed, added) in [
+        ("FooBaz();", "Qux();"),
+        ("FooBaz();", "BarBaz();"),
+        ("Foo(); Foo();", "Bar(); Bar();"),
+        ("ooBar", "xxar"),
+        ("Unrelated();", "Other();"),
+    ] {
+        let expected = decide(&[removed], &[added], &sequential);
+        assert_eq!(
+            decide(&[removed], &[added], &parallel),
+            expected,
+            "{removed} -> {added}"
+        );
+    }
+    assert_eq!(
+        decide(&["FooBaz();"], &["Qux();"], &parallel),
+        Decision::Elide(diffreducer::ElisionReason::Replacement)
+    );
+}
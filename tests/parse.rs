@@ -0,0 +1,276 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    find_parse_errors, parse_file_diffs, parse_file_diffs_with_policy, ChunkBlock, ChunkBlockKind,
+    DiffParser, ParseErrorPolicy, ParserConfig, PatchSet,
+};
+
+fn parse_blocks(body: &str) -> Vec<String> {
+    let input = format!("--- a/foo.cc\n+++ b/foo.cc\n@@ -1,4 +1,4 @@\n{body}");
+    let file_diffs = parse_file_diffs(&input);
+    assert_eq!(file_diffs.len(), 1);
+    assert_eq!(file_diffs[0].chunks.len(), 1);
+    file_diffs[0].chunks[0]
+        .blocks
+        .iter()
+        .map(|block| match block {
+            ChunkBlock::Context(lines) => format!("context {lines:?}"),
+            ChunkBlock::Changed(changed) => {
+                format!("changed {:?} -> {:?}", changed.removed, changed.added)
+            }
+            ChunkBlock::Elided(..) | ChunkBlock::Note(_) | ChunkBlock::Moved(_) => unreachable!(),
+        })
+        .collect()
+}
+
+#[test]
+fn interleaved_changes_form_one_block() {
+    assert_eq!(
+        parse_blocks(" a\n-b\n+B\n-c\n+C\n d\n"),
+        [
+            r#"context ["a"]"#,
+            r#"changed ["b", "c"] -> ["B", "C"]"#,
+            r#"context ["d"]"#,
+        ]
+    );
+}
+
+#[test]
+fn added_then_removed_forms_one_block() {
+    assert_eq!(
+        parse_blocks("+a\n-b\n+c\n-d\n"),
+        [r#"changed ["b", "d"] -> ["a", "c"]"#]
+    );
+}
+
+#[test]
+fn context_separates_changes() {
+    assert_eq!(
+        parse_blocks("-a\n+A\n b\n-c\n+C\n"),
+        [
+            r#"changed ["a"] -> ["A"]"#,
+            r#"context ["b"]"#,
+            r#"changed ["c"] -> ["C"]"#,
+        ]
+    );
+}
+
+#[test]
+fn chunk_annotation() {
+    let input = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,2 +1,2 @@ void MyClass::MyMethod() {
+-a
++b
+@@ -10 +10 @@
+-c
++d
+";
+    let file_diffs = parse_file_diffs(input);
+    let annotations = file_diffs[0]
+        .chunks
+        .iter()
+        .map(|chunk| chunk.annotation_string())
+        .collect::<Vec<_>>();
+    assert_eq!(annotations, [Some("void MyClass::MyMethod() {"), None]);
+}
+
+#[test]
+fn blocks_of_kind() {
+    let input = "--- a/a.cc\n+++ b/a.cc\n@@ -1,4 +1,4 @@\n a\n-b\n+c\n d\n-e\n+f\n";
+    let file_diffs = parse_file_diffs(input);
+    let chunk = &file_diffs[0].chunks[0];
+    assert_eq!(chunk.blocks_of_kind(ChunkBlockKind::Context).count(), 2);
+    assert_eq!(chunk.blocks_of_kind(ChunkBlockKind::Changed).count(), 2);
+    assert_eq!(chunk.blocks_of_kind(ChunkBlockKind::Elided).count(), 0);
+    assert!(chunk
+        .blocks_of_kind(ChunkBlockKind::Changed)
+        .all(|block| block.kind() == ChunkBlockKind::Changed));
+}
+
+#[test]
+fn chunks_in_range() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    let start_lines = |start, end| {
+        file_diffs[0]
+            .chunks_in_range(start, end)
+            .map(|chunk| chunk.start_line().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(start_lines(1, 100), [2, 16, 37]);
+    assert_eq!(start_lines(2, 16), [2, 16]);
+    assert_eq!(start_lines(3, 15), []);
+}
+
+#[test]
+fn parse_errors() {
+    for fixture in [
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/mixed_binary.diff"),
+        include_str!("fixtures/concatenated_diff_r.diff"),
+        include_str!("fixtures/svn.diff"),
+        "",
+        "\n",
+    ] {
+        assert_eq!(find_parse_errors(fixture), [], "{fixture}");
+    }
+
+    let input = concat!(
+        "--- a/a.cc\n",
+        "+++ b/a.cc\n",
+        "@@ -1,3 +1,3 @@\n",
+        " a\n",
+        "*b\n",
+        "@@ -10,2 +10 @@\n",
+        " c\n",
+        "-d\n",
+        "+e\n",
+        "@@ -20,2 +20,2\n",
+        " f\n",
+    );
+    let errors = find_parse_errors(input)
+        .into_iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            "line 5 (byte 41): expected a line starting with ' ', '-', '+' or '\\': \"*b\"",
+            "line 3 (byte 22): the chunk has fewer lines than its header says: \"@@ -1,3 +1,3 @@\"",
+            "line 9 (byte 66): the chunk has more lines than its header says: \"+e\"",
+            "line 10 (byte 69): expected a chunk header like `@@ -1,2 +1,3 @@`: \"@@ -20,2 +20,2\"",
+        ]
+    );
+    // Parsing still works, as well as it can.
+    assert_eq!(parse_file_diffs(input)[0].chunks.len(), 3);
+
+    assert_eq!(
+        find_parse_errors("just some text\n")[0].to_string(),
+        "line 1 (byte 0): no file header found: \"just some text\""
+    );
+}
+
+#[test]
+fn parse_error_policy() {
+    let input = concat!(
+        "--- a/a.cc\n",
+        "+++ b/a.cc\n",
+        "@@ -1,3 +1,3 @@\n",
+        " a\n",
+        "-b\n",
+        "+c\n",
+        "@@ -10,3 +10,3 @@\n",
+        " d\n",
+        "-e\n",
+        "+f\n",
+        " g\n",
+        "--- a/b.cc\n",
+        "+++ b/b.cc\n",
+        "@@ -1 +1 @@\n",
+        "-h\n",
+        "+i\n",
+    );
+    let error = parse_file_diffs_with_policy(input, ParseErrorPolicy::Fail).unwrap_err();
+    assert_eq!(error.line_number, Some(3));
+
+    let file_diffs = parse_file_diffs_with_policy(input, ParseErrorPolicy::Warn).unwrap();
+    let chunk_starts = file_diffs
+        .iter()
+        .map(|file_diff| {
+            let starts = file_diff.chunks.iter().map(|chunk| chunk.start_line());
+            (file_diff.file_name(), starts.collect::<Vec<_>>())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        chunk_starts,
+        [("a.cc", vec![Some(10)]), ("b.cc", vec![Some(1)])]
+    );
+
+    let file_diffs = parse_file_diffs_with_policy(input, ParseErrorPolicy::Ignore).unwrap();
+    assert_eq!(file_diffs[0].chunks.len(), 2);
+
+    // A file is dropped if none of its chunks are left.
+    let input = input.replace("@@ -10,3 +10,3 @@", "@@ -10,4 +10,3 @@");
+    let file_diffs = parse_file_diffs_with_policy(&input, ParseErrorPolicy::Warn).unwrap();
+    assert_eq!(file_diffs.len(), 1);
+    assert_eq!(file_diffs[0].file_name(), "b.cc");
+
+    assert_eq!(
+        "warn".parse::<ParseErrorPolicy>(),
+        Ok(ParseErrorPolicy::Warn)
+    );
+}
+
+#[test]
+fn diff_parser_config() {
+    let input = concat!(
+        "--- a/a.txt\n",
+        "+++ b/a.txt\n",
+        "@@ -1,2 +1,2 @@\n",
+        " a\n",
+        "-b\n",
+        "\\ No newline at end of file\n",
+        "+b\n",
+        "--- a/b.txt\n",
+        "+++ b/b.txt\n",
+        "@@ -1 +1 @@\n",
+        "-c\n",
+        "+d\n",
+    );
+    let file_diffs = DiffParser::new().parse(input).unwrap();
+    assert_eq!(file_diffs.len(), 2);
+    assert!(!PatchSet::from(file_diffs)
+        .to_string()
+        .contains("No newline"));
+
+    let parser = DiffParser::with_config(ParserConfig {
+        preserve_no_newline_markers: true,
+        max_file_diffs: Some(1),
+        ..ParserConfig::default()
+    });
+    let file_diffs = parser.parse(input).unwrap();
+    assert_eq!(file_diffs.len(), 1);
+    assert_eq!(
+        PatchSet::from(file_diffs).to_string(),
+        format!("{}\n", &input[..input.find("--- a/b.txt").unwrap()])
+    );
+}
+
+#[test]
+fn unusual_lines_are_reported_not_fatal() {
+    // An `@@ ` in the middle of a line does not start a chunk, and the text of a no-newline
+    // marker varies, e.g. with the locale.
+    let input = concat!(
+        "--- a/a.txt\n",
+        "+++ b/a.txt\n",
+        "@@ -1,2 +1,2 @@\n",
+        " a @@ b\n",
+        "-c\n",
+        "\\ Kein Zeilenumbruch am Dateiende.\n",
+        "+d\n",
+        "?e\n",
+    );
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(file_diffs[0].chunks.len(), 1);
+    assert_eq!(file_diffs[0].chunks[0].total_raw_lines(), 3);
+
+    let input = input.replace("-c\n", "*c\n");
+    let error = parse_file_diffs_with_policy(&input, ParseErrorPolicy::Fail)
+        .err()
+        .unwrap();
+    assert_eq!(error.line_number, Some(5));
+    assert_eq!(error.byte_offset, input.find("*c"));
+}
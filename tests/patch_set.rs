@@ -0,0 +1,312 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::heuristics::HeuristicPipeline;
+use diffreducer::{parse_file_diffs, Markers, PatchSet};
+
+#[test]
+fn split_by_file_extension() {
+    let input = concat!(
+        include_str!("fixtures/deprecated_attribute_rs.diff"),
+        include_str!("fixtures/svn.diff"),
+        include_str!("fixtures/multiple_hunks.diff"),
+    );
+    let groups = PatchSet::from(parse_file_diffs(input)).split_by_file_extension();
+    let file_names = |extension| {
+        groups[extension]
+            .files
+            .iter()
+            .map(|file_diff| file_diff.file_name())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(groups.len(), 4, "{:?}", groups.keys());
+    assert_eq!(file_names("c"), ["trunk/src/widget.c"]);
+    assert_eq!(file_names("h"), ["trunk/src/widget.h"]);
+    assert_eq!(file_names("txt"), ["b.txt"]);
+    assert_eq!(file_names("rs"), ["attr/widget.rs"]);
+}
+
+#[test]
+fn retain_files() {
+    let input = concat!(
+        include_str!("fixtures/svn.diff"),
+        include_str!("fixtures/multiple_hunks.diff"),
+    );
+    let patch_set = PatchSet::from(parse_file_diffs(input));
+    assert_eq!(patch_set.files.len(), 3);
+    let headers_only = patch_set
+        .clone()
+        .retain_files(|file| file.file_name().ends_with(".h"));
+    assert_eq!(headers_only.files.len(), 1);
+    assert_eq!(headers_only.files[0].file_name(), "trunk/src/widget.h");
+    assert_eq!(patch_set.clone().retain_files(|_| true).files.len(), 3);
+    assert!(patch_set.retain_files(|_| false).files.is_empty());
+}
+
+#[test]
+fn retain_files_by_path_component() {
+    let input = concat!(
+        include_str!("fixtures/svn.diff"),
+        "--- a/foo/test/bar.cc\n+++ b/foo/test/bar.cc\n@@ -1 +1 @@\n-a\n+b\n",
+        "--- a/foo/testing/bar.cc\n+++ b/foo/testing/bar.cc\n@@ -1 +1 @@\n-a\n+b\n",
+    );
+    let file_diffs = parse_file_diffs(input);
+    assert!(file_diffs[2].contains_path_component("test"));
+    assert!(file_diffs[2].contains_path_component("bar.cc"));
+    assert!(!file_diffs[3].contains_path_component("test"));
+    assert_eq!(file_diffs[2].path_components(), ["foo", "test", "bar.cc"]);
+    assert_eq!(
+        file_diffs[0].path_components(),
+        ["trunk", "src", "widget.c"]
+    );
+    let patch_set =
+        PatchSet::from(file_diffs).retain_files(|file| !file.contains_path_component("test"));
+    let file_names = patch_set
+        .files
+        .iter()
+        .map(|file_diff| file_diff.file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        file_names,
+        [
+            "trunk/src/widget.c",
+            "trunk/src/widget.h",
+            "foo/testing/bar.cc"
+        ]
+    );
+}
+
+#[test]
+fn group_identical_hunks() {
+    let b = include_str!("fixtures/multiple_hunks.diff");
+    let c = b
+        .replace("b.txt", "c.txt")
+        .replace("+changed 40", "+changed 41");
+    let d = b.replace("b.txt", "d.txt");
+    let input = format!("{b}{c}{d}");
+    let grouped = PatchSet::from(parse_file_diffs(&input)).group_identical_hunks();
+    let summary = grouped
+        .files
+        .iter()
+        .map(|file_diff| (file_diff.file_name(), file_diff.chunks.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, [("b.txt", 3), ("c.txt", 1)]);
+    let output = grouped.to_string();
+    assert!(
+        output.contains("@@ -16,8 +18,7 @@ line 15\n"),
+        "the note should be counted in the header: {output}"
+    );
+    assert!(
+        output.contains(" [diffreducer: identical hunk also in c.txt, d.txt]\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains(" [diffreducer: identical hunk also in d.txt]\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn merge() {
+    let a = concat!(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -10 +10 @@\n-x\n+y\n",
+        "--- a/b.cc\n+++ b/b.cc\n@@ -1 +1 @@\n-b\n+B\n",
+    );
+    let b = concat!(
+        "--- a/c.cc\n+++ b/c.cc\n@@ -1 +1 @@\n-c\n+C\n",
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1 +1 @@\n-w\n+v\n",
+        "--- a/b.cc\n+++ b/b.cc\n@@ -1 +1 @@\n-b\n+B\n",
+    );
+    let merged = PatchSet::merge(
+        PatchSet::from(parse_file_diffs(a)),
+        PatchSet::from(parse_file_diffs(b)),
+    );
+    let summary = merged
+        .files
+        .iter()
+        .map(|file_diff| {
+            (
+                file_diff.file_name(),
+                file_diff
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.start_line().unwrap())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // Files keep the order they were first seen in, and b.cc's shared chunk is kept once.
+    assert_eq!(
+        summary,
+        [("a.cc", vec![1, 10]), ("b.cc", vec![1]), ("c.cc", vec![1])]
+    );
+
+    // Disjoint sets are simply concatenated.
+    let merged = PatchSet::merge(
+        PatchSet::from(parse_file_diffs(&a[..a.find("--- a/b.cc").unwrap()])),
+        PatchSet::from(parse_file_diffs(&b[..b.find("--- a/a.cc").unwrap()])),
+    );
+    assert_eq!(
+        merged.to_string(),
+        PatchSet::from(parse_file_diffs(&format!(
+            "{}{}",
+            &a[..a.find("--- a/b.cc").unwrap()],
+            &b[..b.find("--- a/a.cc").unwrap()]
+        )))
+        .to_string()
+    );
+}
+
+#[test]
+fn diff() {
+    let a = concat!(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1 +1 @@\n-x\n+y\n",
+        "--- a/b.cc\n+++ b/b.cc\n@@ -1 +1 @@\n-b\n+B\n",
+        "--- a/same.cc\n+++ b/same.cc\n@@ -1 +1 @@\n-s\n+S\n",
+    );
+    let b = concat!(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1 +1 @@\n-x\n+y\n@@ -20 +20 @@\n-u\n+v\n",
+        "--- a/same.cc\n+++ b/same.cc\n@@ -1 +1 @@\n-s\n+S\n",
+        "--- a/c.cc\n+++ b/c.cc\n@@ -1 +1 @@\n-c\n+C\n",
+    );
+    let (a, b) = (
+        PatchSet::from(parse_file_diffs(a)),
+        PatchSet::from(parse_file_diffs(b)),
+    );
+    let diff = PatchSet::diff(&a, &b).unwrap();
+    let summary = diff
+        .files
+        .iter()
+        .map(|file_diff| {
+            (
+                file_diff.file_name(),
+                file_diff
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.start_line().unwrap())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [("a.cc", vec![20]), ("b.cc", vec![1]), ("c.cc", vec![1])]
+    );
+    assert!(PatchSet::diff(&a, &a).unwrap().files.is_empty());
+
+    // Both sides change line 1 of a.cc differently.
+    let c = PatchSet::from(parse_file_diffs(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1 +1 @@\n-x\n+z\n",
+    ));
+    let overlap = PatchSet::diff(&a, &c).err().unwrap();
+    assert_eq!(overlap.file_name, "a.cc");
+    assert_eq!(overlap.line, 1);
+}
+
+#[test]
+fn custom_markers() {
+    let input = "--- a/foo.cc\n+++ b/foo.cc\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+    let markers = Markers {
+        context: '=',
+        added: '>',
+        removed: '<',
+        ..Markers::UNIFIED
+    };
+    assert_eq!(markers.validate(), Ok(()));
+    assert_eq!(
+        PatchSet::from(parse_file_diffs(input))
+            .display_with(&markers)
+            .to_string(),
+        "--- a/foo.cc\n+++ b/foo.cc\n@@ -1,2 +1,2 @@\n=a\n<b\n>c\n\n"
+    );
+    assert!(Markers {
+        added: '-',
+        ..Markers::UNIFIED
+    }
+    .validate()
+    .is_err());
+}
+
+#[test]
+fn ungrouped() {
+    let input = include_str!("fixtures/multiple_hunks.diff");
+    let ungrouped = PatchSet::from(parse_file_diffs(input)).ungrouped();
+    assert_eq!(ungrouped.files.len(), 3);
+    for file_diff in &ungrouped.files {
+        assert_eq!(file_diff.file_name(), "b.txt");
+        assert_eq!(file_diff.chunks.len(), 1);
+    }
+    assert_eq!(ungrouped.to_string().matches("\n--- a/b.txt\n").count(), 3);
+}
+
+#[test]
+fn standalone_patch() {
+    let input = include_str!("fixtures/multiple_hunks.diff");
+    let file_diffs = parse_file_diffs(input);
+    let chunk = &file_diffs[0].chunks[1];
+    let patch = chunk.to_standalone_patch("b.txt");
+    assert!(
+        patch.starts_with("--- a/b.txt\n+++ b/b.txt\n@@ "),
+        "{patch}"
+    );
+
+    let reparsed = parse_file_diffs(&patch);
+    assert_eq!(reparsed.len(), 1);
+    assert_eq!(reparsed[0].file_name(), "b.txt");
+    assert_eq!(reparsed[0].chunks.len(), 1);
+    assert_eq!(reparsed[0].chunks[0].to_string(), chunk.to_string());
+}
+
+#[test]
+fn detect_moves() {
+    let input = include_str!("fixtures/moved_function.diff");
+    let markers = Markers {
+        moved_removed: '<',
+        moved_added: '>',
+        ..Markers::UNIFIED
+    };
+    assert_eq!(markers.validate(), Ok(()));
+    let output = PatchSet::from(parse_file_diffs(input))
+        .detect_moves(&HeuristicPipeline::default())
+        .display_with(&markers)
+        .to_string();
+    // The indentation changed, but the function was only moved to b.cc.
+    assert!(
+        output.contains("\n<int Helper(int x) {\n<  return x * 2;\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("\n>int Helper(int x) {\n>    return x * 2;\n"),
+        "{output}"
+    );
+    // Nothing removed anywhere matches the new c.cc.
+    assert!(output.contains("\n+int Gone() {\n"), "{output}");
+
+    // By default, moved lines are rendered like any other change.
+    let output = PatchSet::from(parse_file_diffs(input))
+        .detect_moves(&HeuristicPipeline::default())
+        .to_string();
+    assert_eq!(output, PatchSet::from(parse_file_diffs(input)).to_string());
+}
+
+#[test]
+fn total_net_delta() {
+    let input = concat!(
+        "--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,3 @@\n x\n-a\n+b\n+c\n",
+        "--- a/b.cc\n+++ b/b.cc\n@@ -1,3 +1 @@\n-d\n-e\n-f\n+g\n",
+    );
+    let patch_set = PatchSet::from(parse_file_diffs(input));
+    assert_eq!(patch_set.total_net_delta(), -1);
+    assert_eq!(PatchSet::default().total_net_delta(), 0);
+}
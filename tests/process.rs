@@ -0,0 +1,247 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    parse_file_diffs, process_file_diffs, process_file_diffs_preserving_context,
+    process_file_diffs_with_stats, ElisionReason, ProcessConfig, Replacement, Stats,
+};
+use std::collections::HashSet;
+
+#[test]
+fn extract_changed_only() {
+    let input = include_str!("fixtures/multiple_hunks.diff");
+    let file_diff = &parse_file_diffs(input)[0];
+    let changed_only = file_diff.extract_changed_only();
+    assert_eq!(changed_only.chunks.len(), file_diff.chunks.len());
+    for (chunk, original) in changed_only.chunks.iter().zip(&file_diff.chunks) {
+        assert_eq!(chunk.context_line_count(), 0);
+        assert_eq!(chunk.start_line(), original.start_line());
+    }
+    let output = changed_only.to_string();
+    assert!(
+        output.contains(concat!(
+            "+++ b/b.txt\n",
+            "@@ -2,1 +2,3 @@ line 1\n",
+            "-  NOTREACHED_NORETURN();\n",
+            "+  NOTREACHED();\n",
+            "+added A\n",
+            "+added B\n",
+            "@@ -16,"
+        )),
+        "{output}"
+    );
+}
+
+#[test]
+fn elide_identical_removed_added() {
+    let input = "--- a/a.cc\n+++ b/a.cc\n@@ -1,3 +1,3 @@\n x\n-int y;\n+int y;\n z\n";
+    let mut stats = Stats::default();
+    // Lines this long are otherwise kept without comparing them.
+    let config = ProcessConfig::builder().max_line_length(3).build().unwrap();
+    let output = process_file_diffs_with_stats(parse_file_diffs(input), &config, &mut stats);
+    assert!(output.is_empty());
+    assert_eq!(stats.elisions_by_reason[&ElisionReason::Identical], 1);
+
+    let config = ProcessConfig::builder()
+        .max_line_length(3)
+        .elide_identical_removed_added(false)
+        .build()
+        .unwrap();
+    let output = process_file_diffs(parse_file_diffs(input), &config);
+    assert_eq!(output.len(), 1);
+    assert!(output[0].to_string().contains("-int y;\n+int y;\n"));
+}
+
+#[test]
+fn only_reduced_files() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+    );
+    let config = ProcessConfig {
+        only_reduced_files: true,
+        ..ProcessConfig::default()
+    };
+    let file_names = |config: &ProcessConfig| {
+        process_file_diffs(parse_file_diffs(input), config)
+            .iter()
+            .map(|file_diff| file_diff.file_name().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        file_names(&ProcessConfig::default()),
+        ["b.txt", "trunk/src/widget.h"]
+    );
+    // Nothing in widget.h was elided, and widget.c was dropped since everything in it was.
+    assert_eq!(file_names(&config), ["b.txt"]);
+}
+
+#[test]
+fn huge_minified_line() {
+    let line = "0,".repeat(4 << 20);
+    let input = format!(
+        "--- a/bundle.js\n+++ b/bundle.js\n@@ -1,1 +1,1 @@\n-var a=[{line}];\n+var b=[{line}];\n"
+    );
+    let output = process_file_diffs(parse_file_diffs(&input), &ProcessConfig::default());
+    assert_eq!(output.len(), 1);
+    assert_eq!(output[0].chunks.len(), 1);
+}
+
+#[test]
+fn preserving_context() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+    );
+    let output =
+        process_file_diffs_preserving_context(parse_file_diffs(input), &ProcessConfig::default());
+    assert_eq!(
+        output
+            .iter()
+            .map(|(file_diff, changed)| (file_diff.file_name(), changed.as_slice()))
+            .collect::<Vec<_>>(),
+        [
+            ("b.txt", [true, true, true].as_slice()),
+            ("trunk/src/widget.c", &[false]),
+            ("trunk/src/widget.h", &[true]),
+        ]
+    );
+    // The elided replacement in widget.c is not rendered, but its chunk still is.
+    assert!(output[1].0.to_string().ends_with(" int widget_show(struct widget *w)\n {\n   if (!w)\n   w->visible = 1;\n   return 0;\n }\n"));
+}
+
+#[test]
+fn min_file_changes_drops_small_files() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+    );
+    let config = ProcessConfig {
+        min_file_changes: Some(2),
+        ..ProcessConfig::default()
+    };
+    let output = process_file_diffs(parse_file_diffs(input), &config);
+    // widget.h only adds one line.
+    assert_eq!(
+        output
+            .iter()
+            .map(|file_diff| file_diff.file_name())
+            .collect::<Vec<_>>(),
+        ["b.txt"]
+    );
+    assert_eq!(output[0].total_changed_lines(), (2, 3));
+}
+
+#[test]
+fn max_file_changes_passes_large_files_through() {
+    let input = include_str!("fixtures/multiple_hunks.diff");
+    let config = ProcessConfig {
+        max_file_changes: Some(6),
+        ..ProcessConfig::default()
+    };
+    let output = process_file_diffs(parse_file_diffs(input), &config);
+    assert_eq!(output[0].to_string(), input);
+}
+
+#[test]
+fn include_unchanged_files() {
+    let input = include_str!("fixtures/svn.diff");
+    let config = ProcessConfig {
+        include_unchanged_files: true,
+        ..ProcessConfig::default()
+    };
+    let file_diffs = process_file_diffs(parse_file_diffs(input), &config);
+    assert_eq!(
+        file_diffs
+            .iter()
+            .map(|file_diff| file_diff.file_name())
+            .collect::<Vec<_>>(),
+        ["trunk/src/widget.c", "trunk/src/widget.h"]
+    );
+    // Everything in widget.c was elided, so only its header is left.
+    assert!(file_diffs[0].chunks.is_empty());
+    assert!(!file_diffs[1].chunks.is_empty());
+}
+
+#[test]
+fn replaced_symbols() {
+    let input = concat!(
+        "--- a/foo.cc\n",
+        "+++ b/foo.cc\n",
+        "@@ -1,2 +1,2 @@\n",
+        "-  NOTREACHED_NORETURN() << \"a\";\n",
+        "+  NOTREACHED() << \"b\";\n",
+        " }\n",
+    );
+    let replacements = vec![
+        Replacement::new("NOTREACHED_NORETURN", "NOTREACHED"),
+        Replacement::new("base::Value", "base::Dict"),
+    ];
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs[0].replaced_symbols(&replacements),
+        HashSet::from(["NOTREACHED_NORETURN".to_owned()])
+    );
+
+    // Regex rules are matched as patterns, not as literal text.
+    let input = concat!(
+        "--- a/foo.cc\n",
+        "+++ b/foo.cc\n",
+        "@@ -1,3 +1,3 @@\n",
+        "-  Wait(kTimeout5);\n",
+        "-  Sleep(kDelay(\\d+));\n",
+        "+  Wait(kTimeoutMs5);\n",
+        "+  Sleep(kDelayMs);\n",
+        " }\n",
+    );
+    let replacements = vec![
+        Replacement::regex(r"\bkTimeout(\d+)\b", "kTimeoutMs$1").unwrap(),
+        Replacement::regex(r"\bkDelay(\d+)\b", "kDelayMs$1").unwrap(),
+        Replacement::regex(r"\bkTimeout(\d+)\b", "kTimeoutSec$1").unwrap(),
+    ];
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs[0].replaced_symbols(&replacements),
+        HashSet::from([r"\bkTimeout(\d+)\b".to_owned()])
+    );
+}
+
+#[test]
+fn min_change_ratio() {
+    let input = concat!(
+        "--- a/a.cc\n",
+        "+++ b/a.cc\n",
+        "@@ -1,9 +1,9 @@\n",
+        " 1\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n 9\n",
+        "@@ -20,2 +20,2 @@\n",
+        " 20\n-21\n+twenty-one\n",
+    );
+    let file_diffs = parse_file_diffs(input);
+    assert_eq!(file_diffs[0].chunks[0].change_ratio(), 0.2);
+    assert_eq!(file_diffs[0].chunks[1].change_ratio(), 2.0 / 3.0);
+
+    let config = ProcessConfig::builder()
+        .min_change_ratio(Some(0.25))
+        .build()
+        .unwrap();
+    let mut stats = Stats::default();
+    let output = process_file_diffs_with_stats(file_diffs, &config, &mut stats);
+    assert_eq!(output[0].chunks.len(), 1);
+    assert_eq!(output[0].chunks[0].start_line(), Some(20));
+    assert_eq!(stats.elisions_by_reason[&ElisionReason::LowChangeRatio], 1);
+    assert_eq!(
+        "low-change-ratio".parse::<ElisionReason>(),
+        Ok(ElisionReason::LowChangeRatio)
+    );
+}
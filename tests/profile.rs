@@ -0,0 +1,47 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use diffreducer::{parse_file_diffs, process_file_diffs, profile, ProcessConfig};
+
+const INPUT: &str = "\
+--- a/a.cc
++++ b/a.cc
+@@ -1,3 +1,3 @@
+ a
+-  NOTREACHED_NORETURN();
++  NOTREACHED();
+ b
+";
+
+fn process() {
+    process_file_diffs(parse_file_diffs(INPUT), &ProcessConfig::default());
+}
+
+// Everything runs in one test since the timer and the switch are process-wide.
+#[test]
+fn normalization_time_only_accumulates_when_enabled() {
+    process();
+    assert_eq!(profile::normalization_time(), Duration::ZERO);
+
+    profile::set_enabled(true);
+    process();
+    let enabled = profile::normalization_time();
+    assert!(enabled > Duration::ZERO);
+
+    profile::set_enabled(false);
+    process();
+    assert_eq!(profile::normalization_time(), enabled);
+}
@@ -0,0 +1,83 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const INPUT: &str = concat!(
+    "--- a/foo.cc\n",
+    "+++ b/foo.cc\n",
+    "@@ -1,3 +1,3 @@\n",
+    " void Foo() {\n",
+    "-  NOTREACHED_NORETURN();\n",
+    "+  NOTREACHED();\n",
+    "@@ -10,3 +10,3 @@\n",
+    " void Bar() {\n",
+    "-  int y = 2;\n",
+    "+  int y = 3;\n",
+);
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("diffreducer-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("top.patch"), INPUT).unwrap();
+    fs::write(dir.join("nested/inner.diff"), INPUT).unwrap();
+    fs::write(dir.join("nested/notes.txt"), INPUT).unwrap();
+    dir
+}
+
+fn diffreducer(args: &[&str]) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+        .args(args)
+        .status()
+        .unwrap()
+        .success()
+}
+
+#[test]
+fn writes_results_next_to_inputs() {
+    let dir = temp_dir("recursive");
+    assert!(diffreducer(&["--recursive", dir.to_str().unwrap()]));
+    for result in ["top.reduced.patch", "nested/inner.reduced.diff"] {
+        let output = fs::read_to_string(dir.join(result)).unwrap();
+        assert!(!output.contains("NOTREACHED"), "{output}");
+        assert!(output.contains("+  int y = 3;\n"), "{output}");
+    }
+    assert!(!dir.join("nested/notes.reduced.txt").exists());
+
+    // Earlier results are not reduced again.
+    assert!(diffreducer(&["--recursive", dir.to_str().unwrap()]));
+    assert!(!dir.join("top.reduced.reduced.patch").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reports_failures_and_continues() {
+    let dir = temp_dir("recursive-failure");
+    fs::write(dir.join("broken.patch"), b"\xff\xfe").unwrap();
+    let output_dir = dir.with_extension("out");
+    assert!(!diffreducer(&[
+        "--recursive",
+        dir.to_str().unwrap(),
+        "--output-dir",
+        output_dir.to_str().unwrap(),
+    ]));
+    assert!(output_dir.join("top.patch").exists());
+    assert!(output_dir.join("nested/inner.diff").exists());
+    assert!(!output_dir.join("broken.patch").exists());
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&output_dir).unwrap();
+}
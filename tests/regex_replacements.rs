@@ -0,0 +1,90 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    decide, rules_from_toml, ConfigError, Decision, ElisionReason, ProcessConfig, Replacement,
+};
+
+#[test]
+fn capture_groups() {
+    // With the default, sequential matching as well as with the parallel matcher.
+    for parallel_replacements in [false, true] {
+        check_capture_groups(parallel_replacements);
+    }
+}
+
+fn check_capture_groups(parallel_replacements: bool) {
+    let config = ProcessConfig::builder()
+        .replacements(vec![
+            Replacement::new("NOTREACHED_NORETURN", "NOTREACHED"),
+            Replacement::regex(r"base::StringPrintf\(", "absl::StrFormat(").unwrap(),
+            Replacement::regex(r"\bkTimeout(\d+)\b", "kTimeoutMs$1").unwrap(),
+        ])
+        .parallel_replacements(parallel_replacements)
+        .build()
+        .unwrap();
+    for (removed, added) in [
+        (
+            r#"auto s = base::StringPrintf("%d", kTimeout5);"#,
+            r#"auto s = absl::StrFormat("%d", kTimeoutMs5);"#,
+        ),
+        ("  NOTREACHED_NORETURN();", "  NOTREACHED();"),
+    ] {
+        assert_eq!(
+            decide(&[removed], &[added], &config),
+            Decision::Elide(ElisionReason::Replacement),
+            "{removed}"
+        );
+    }
+    assert_eq!(
+        decide(&["kTimeout5;"], &["kTimeoutMs6;"], &config),
+        Decision::Keep
+    );
+}
+
+#[test]
+fn invalid_patterns_are_rejected_up_front() {
+    assert!(Replacement::regex("(", "x").is_err());
+    let error = ProcessConfig::builder()
+        .replacements(vec![Replacement::regex("a(b)", "$2").unwrap()])
+        .build()
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, ConfigError::InvalidReplacement(invalid) if invalid.before == "a(b)"),
+        "{error}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid replacement \"a(b)\": `after` refers to capture group $2 but the pattern has 1 \
+         groups"
+    );
+
+    let rules = rules_from_toml(concat!(
+        "[[rules]]\n",
+        "before = 'kTimeout(\\d+)'\n",
+        "after = 'kTimeoutMs$1'\n",
+        "regex = true\n",
+    ))
+    .unwrap();
+    assert!(rules[0].pattern.is_some());
+    assert_eq!(rules[0].to_string(), r"/kTimeout(\d+)/ -> kTimeoutMs$1");
+    let error = rules_from_toml("[[rules]]\nbefore = '('\nafter = 'x'\nregex = true\n")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.starts_with("invalid rules: `rules[0]`: invalid pattern: "),
+        "{error}"
+    );
+}
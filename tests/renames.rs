@@ -0,0 +1,172 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{detect_renames, parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+const INPUT: &str = concat!(
+    "diff --git a/old.cc b/old.cc\n",
+    "deleted file mode 100644\n",
+    "index 1234567..0000000\n",
+    "--- a/old.cc\n",
+    "+++ /dev/null\n",
+    "@@ -1,5 +0,0 @@\n",
+    "-void Foo() {\n",
+    "-  NOTREACHED_NORETURN();\n",
+    "-}\n",
+    "-\n",
+    "-int x = 1;\n",
+    "diff --git a/other.cc b/other.cc\n",
+    "new file mode 100644\n",
+    "index 0000000..1234567\n",
+    "--- /dev/null\n",
+    "+++ b/other.cc\n",
+    "@@ -0,0 +1 @@\n",
+    "+int unrelated = 0;\n",
+    "diff --git a/new.cc b/new.cc\n",
+    "new file mode 100644\n",
+    "index 0000000..89abcde\n",
+    "--- /dev/null\n",
+    "+++ b/new.cc\n",
+    "@@ -0,0 +1,5 @@\n",
+    "+void Foo() {\n",
+    "+  NOTREACHED();\n",
+    "+}\n",
+    "+\n",
+    "+int x = 2;\n",
+);
+
+#[test]
+fn detect_deleted_and_added_pairs() {
+    let file_diffs = parse_file_diffs(INPUT);
+    assert!(file_diffs[0].is_deleted_file());
+    assert!(file_diffs[2].is_new_file());
+    let candidates = detect_renames(&file_diffs);
+    assert_eq!(candidates.len(), 1);
+    let candidate = &candidates[0];
+    assert_eq!((candidate.deleted, candidate.added), (0, 2));
+    assert_eq!((&*candidate.from, &*candidate.to), ("old.cc", "new.cc"));
+    // 3 of the 7 distinct lines are in both.
+    assert_eq!(candidate.similarity, 3.0 / 7.0);
+}
+
+#[test]
+fn renames_are_reduced_like_other_changes() {
+    let config = ProcessConfig {
+        rename_detection_threshold: Some(0.4),
+        ..ProcessConfig::default()
+    };
+    let output = PatchSet::from(process_file_diffs(parse_file_diffs(INPUT), &config)).to_string();
+    assert_eq!(
+        output,
+        concat!(
+            "diff --git a/other.cc b/other.cc\n",
+            "new file mode 100644\n",
+            "index 0000000..1234567\n",
+            "--- /dev/null\n",
+            "+++ b/other.cc\n",
+            "@@ -0,0 +1 @@\n",
+            "+int unrelated = 0;\n",
+            "\n",
+            "diff --git a/old.cc b/new.cc\n",
+            "similarity index 42%\n",
+            "rename from old.cc\n",
+            "rename to new.cc\n",
+            "--- a/old.cc\n",
+            "+++ b/new.cc\n",
+            "@@ -1,5 +1,5 @@\n",
+            " void Foo() {\n",
+            " }\n",
+            " \n",
+            "-int x = 1;\n",
+            "+int x = 2;\n",
+            "\n",
+        )
+    );
+
+    // Below the threshold, nothing changes.
+    let config = ProcessConfig {
+        rename_detection_threshold: Some(0.5),
+        ..ProcessConfig::default()
+    };
+    let output = PatchSet::from(process_file_diffs(parse_file_diffs(INPUT), &config)).to_string();
+    assert!(output.contains("deleted file mode"), "{output}");
+}
+
+#[test]
+fn identical_content_is_a_pure_rename() {
+    let input = INPUT
+        .replace("+  NOTREACHED();", "+  NOTREACHED_NORETURN();")
+        .replace("+int x = 2;", "+int x = 1;");
+    let config = ProcessConfig {
+        rename_detection_threshold: Some(1.0),
+        ..ProcessConfig::default()
+    };
+    let output = PatchSet::from(process_file_diffs(parse_file_diffs(&input), &config)).to_string();
+    assert!(
+        output.ends_with(concat!(
+            "diff --git a/old.cc b/new.cc\n",
+            "similarity index 100%\n",
+            "rename from old.cc\n",
+            "rename to new.cc\n",
+            "\n",
+        )),
+        "{output}"
+    );
+}
+
+#[test]
+fn git_rename_and_copy_headers_are_kept() {
+    let pure_rename = concat!(
+        "diff --git a/a.cc b/b.cc\n",
+        "similarity index 100%\n",
+        "rename from a.cc\n",
+        "rename to b.cc\n",
+    );
+    let copy = concat!(
+        "diff --git a/c.cc b/d.cc\n",
+        "similarity index 90%\n",
+        "copy from c.cc\n",
+        "copy to d.cc\n",
+        "index 1111111..2222222 100644\n",
+        "--- a/c.cc\n",
+        "+++ b/d.cc\n",
+    );
+    let input = format!(
+        "{pure_rename}{copy}{}",
+        concat!(
+            "@@ -1,2 +1,2 @@\n",
+            " x\n",
+            "-  NOTREACHED_NORETURN();\n",
+            "+  NOTREACHED();\n",
+            "diff --git a/e.cc b/e.cc\n",
+            "--- a/e.cc\n",
+            "+++ b/e.cc\n",
+            "@@ -1 +1 @@\n",
+            "-a\n",
+            "+b\n",
+        )
+    );
+    let file_diffs = parse_file_diffs(&input);
+    assert_eq!(file_diffs.len(), 3);
+    assert!(file_diffs[0].chunks.is_empty());
+    assert!(file_diffs[0].is_rename_or_copy());
+    assert!(file_diffs[1].is_rename_or_copy());
+    assert!(!file_diffs[2].is_rename_or_copy());
+
+    // The copy is kept even though its only change is elided.
+    let output = process_file_diffs(file_diffs, &ProcessConfig::default());
+    assert_eq!(output.len(), 3);
+    assert_eq!(output[0].to_string(), pure_rename);
+    assert_eq!(output[1].to_string(), copy);
+}
@@ -0,0 +1,123 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{rules_from_toml, ConfigError, ProcessConfig, Replacement, ReplacementRegistry};
+
+#[test]
+fn default_set() {
+    let config = ProcessConfig::builder().build().unwrap();
+    assert_eq!(
+        config.replacement_set.as_deref(),
+        Some(ReplacementRegistry::DEFAULT_SET)
+    );
+    assert_eq!(config.replacements.len(), 1);
+    assert_eq!(config.replacements[0].before, "NOTREACHED_NORETURN");
+}
+
+#[test]
+fn named_set() {
+    let mut registry = ReplacementRegistry::default();
+    registry.register(
+        "base-rename",
+        "2024-05-01",
+        vec![Replacement::new("base::Foo", "base::Bar")],
+    );
+    assert_eq!(
+        registry.names(),
+        ["base-rename", ReplacementRegistry::DEFAULT_SET]
+    );
+    let config = ProcessConfig::builder()
+        .registry(registry)
+        .replacement_set("base-rename")
+        .replacement(Replacement::new("Qux", "Quux"))
+        .build()
+        .unwrap();
+    let rules = config
+        .replacements
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(rules, ["base::Foo -> base::Bar", "Qux -> Quux"]);
+}
+
+#[test]
+fn unknown_set() {
+    let error = ProcessConfig::builder()
+        .replacement_set("nope")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        ConfigError::UnknownReplacementSet {
+            name: "nope".to_owned(),
+            known: vec![ReplacementRegistry::DEFAULT_SET.to_owned()],
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "unknown replacement set \"nope\"; known sets: chromium-notreached"
+    );
+}
+
+#[test]
+fn explicit_replacements_are_not_a_set() {
+    let config = ProcessConfig::builder()
+        .replacements(vec![Replacement::new("a", "b")])
+        .build()
+        .unwrap();
+    assert_eq!(config.replacement_set, None);
+    assert_eq!(config.replacements.len(), 1);
+}
+
+#[test]
+fn rules_from_toml_file() {
+    let rules = rules_from_toml(concat!(
+        "[[rules]]\n",
+        "before = \"NOTREACHED_NORETURN\"\n",
+        "after = \"NOTREACHED\"\n",
+        "\n",
+        "[[rules]]\n",
+        "before = \"base::Value\"\n",
+        "after = \"base::Value::Dict\"\n",
+    ))
+    .unwrap();
+    let rules = rules
+        .iter()
+        .map(|rule| (&*rule.before, &*rule.after))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rules,
+        [
+            ("NOTREACHED_NORETURN", "NOTREACHED"),
+            ("base::Value", "base::Value::Dict")
+        ]
+    );
+    assert!(rules_from_toml("").unwrap().is_empty());
+
+    let error = |rules| rules_from_toml(rules).unwrap_err().to_string();
+    assert_eq!(
+        error("[[rules]]\nbefore = \"a\"\nafter = \"b\"\n[[rules]]\nbefore = \"c\"\n"),
+        "invalid rules: `rules[1]`: missing `after`"
+    );
+    assert_eq!(
+        error("[[rules]]\nbefore = \"a\"\nafter = 1\n"),
+        "invalid rules: `rules[0]`: `after` is not a string"
+    );
+    assert_eq!(
+        error("[[rules]]\nbefore = \"a\"\nafter = \"b\"\nreason = \"c\"\n"),
+        "invalid rules: `rules[0]`: unknown key `reason`"
+    );
+    assert_eq!(error("rule = 1\n"), "invalid rules: unknown key `rule`");
+}
@@ -0,0 +1,43 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs, PatchSet, ProcessConfig};
+
+fn reduce(input: &str, elide_scope_wrappers: bool) -> String {
+    let config = ProcessConfig {
+        elide_scope_wrappers,
+        ..ProcessConfig::default()
+    };
+    PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+}
+
+#[test]
+fn balanced_wrapper_is_elided() {
+    let output = reduce(include_str!("fixtures/scope_wrapper.diff"), true);
+    assert!(!output.contains("widget.cc"), "{output}");
+}
+
+#[test]
+fn unbalanced_wrapper_is_kept() {
+    // The namespace in gadget.cc is closed outside the diff.
+    let output = reduce(include_str!("fixtures/scope_wrapper.diff"), true);
+    assert!(output.contains("+namespace ui {\n"), "{output}");
+    assert!(output.contains("+  return size_ + 1;\n"), "{output}");
+}
+
+#[test]
+fn wrappers_are_kept_by_default() {
+    let output = reduce(include_str!("fixtures/scope_wrapper.diff"), false);
+    assert!(output.contains("+}  // namespace ui\n"), "{output}");
+}
@@ -0,0 +1,40 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::similarity::{jaccard_similarity, SimilarityMetric};
+
+#[test]
+fn jaccard() {
+    assert_eq!(jaccard_similarity(&[], &[]), 1.0);
+    assert_eq!(jaccard_similarity(&["a", "b"], &["b", "a"]), 1.0);
+    assert_eq!(jaccard_similarity(&["a", "b"], &["c"]), 0.0);
+    assert_eq!(jaccard_similarity(&["a", "b", "c"], &["a", "b", "d"]), 0.5);
+    // Repeated tokens count as many times as they appear.
+    assert_eq!(jaccard_similarity(&["a", "a"], &["a"]), 0.5);
+}
+
+#[test]
+fn metrics() {
+    let a = ["a", "b", "c", "d"];
+    let b = ["b", "a", "c", "e"];
+    assert_eq!(SimilarityMetric::Exact.similarity(&a, &a), 1.0);
+    assert_eq!(SimilarityMetric::Exact.similarity(&a, &b), 0.0);
+    assert_eq!(SimilarityMetric::Levenshtein.similarity(&a, &b), 0.25);
+    assert_eq!(SimilarityMetric::Jaccard.similarity(&a, &b), 0.6);
+    assert_eq!(SimilarityMetric::Lcs.similarity(&a, &b), 0.5);
+    for metric in SimilarityMetric::ALL {
+        assert_eq!(metric.similarity(&[], &[]), 1.0);
+        assert_eq!(metric.to_string().parse(), Ok(*metric));
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, OverlappingChunks};
+
+#[test]
+fn sorts_chunks_by_start_line() {
+    let input = concat!(
+        "--- a/foo.txt\n",
+        "+++ b/foo.txt\n",
+        "@@ -20,2 +20,2 @@\n",
+        " twenty\n",
+        "-b\n",
+        "+B\n",
+        "@@ -5,2 +5,2 @@\n",
+        " five\n",
+        "-a\n",
+        "+A\n",
+    );
+    let mut file_diffs = parse_file_diffs(input);
+    assert_eq!(file_diffs[0].sort_chunks(), Ok(()));
+    let starts = file_diffs[0]
+        .chunks
+        .iter()
+        .map(|chunk| chunk.start_line())
+        .collect::<Vec<_>>();
+    assert_eq!(starts, vec![Some(5), Some(20)]);
+}
+
+#[test]
+fn reports_overlapping_chunks() {
+    let input = concat!(
+        "--- a/foo.txt\n",
+        "+++ b/foo.txt\n",
+        "@@ -6,2 +6,2 @@\n",
+        " six\n",
+        "-b\n",
+        "+B\n",
+        "@@ -5,2 +5,2 @@\n",
+        " five\n",
+        "-a\n",
+        "+A\n",
+    );
+    let mut file_diffs = parse_file_diffs(input);
+    assert_eq!(
+        file_diffs[0].sort_chunks(),
+        Err(OverlappingChunks {
+            file_name: "foo.txt".to_owned(),
+            line: 6,
+        })
+    );
+}
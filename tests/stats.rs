@@ -0,0 +1,87 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, process_file_diffs_with_stats, ProcessConfig, Stats};
+
+#[test]
+fn elisions_are_attributed_to_rules() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+        include_str!("fixtures/deprecated_attribute_java.diff"),
+    );
+    let config = ProcessConfig {
+        attribute_patterns: ProcessConfig::deprecated_attribute_patterns(),
+        ..ProcessConfig::default()
+    };
+    let mut stats = Stats::default();
+    process_file_diffs_with_stats(parse_file_diffs(input), &config, &mut stats);
+    assert_eq!(stats.files, 4);
+    assert_eq!(stats.elided_blocks, 3);
+    assert_eq!(
+        stats.display_summary(),
+        "3/8 blocks filtered across 3/4 files"
+    );
+    assert_eq!(
+        stats
+            .per_file
+            .iter()
+            .map(|file| (file.file_name.as_str(), file.elided_blocks, file.kept))
+            .collect::<Vec<_>>(),
+        [
+            ("b.txt", 1, true),
+            ("trunk/src/widget.c", 1, false),
+            ("trunk/src/widget.h", 0, true),
+            ("attr/Widget.java", 1, true),
+        ]
+    );
+    assert_eq!(
+        stats.elisions_by_rule.iter().collect::<Vec<_>>(),
+        [
+            (&"attribute-only".to_owned(), &1),
+            (
+                &"replacement NOTREACHED_NORETURN -> NOTREACHED".to_owned(),
+                &2
+            ),
+        ]
+    );
+    assert_eq!(
+        stats.dominant_rules(50.0),
+        [("replacement NOTREACHED_NORETURN -> NOTREACHED", 200.0 / 3.0)]
+    );
+}
+
+#[test]
+fn output_line_counts() {
+    let input = concat!(
+        include_str!("fixtures/multiple_hunks.diff"),
+        include_str!("fixtures/svn.diff"),
+    );
+    let config = ProcessConfig {
+        context_lines: Some(1),
+        ..ProcessConfig::default()
+    };
+    let mut stats = Stats::default();
+    let output = process_file_diffs_with_stats(parse_file_diffs(input), &config, &mut stats);
+    assert_eq!(output[0].total_context_lines(), 6);
+    assert_eq!(output[0].chunks[0].context_line_count(), 2);
+    assert_eq!(stats.output_files, 2);
+    assert_eq!(
+        stats.display_sizes(),
+        "files: 3 in, 2 out; chunks: 5 in, 4 out"
+    );
+    assert_eq!(stats.output_context_lines, 8);
+    assert_eq!(stats.output_removed_lines, 2);
+    assert_eq!(stats.output_added_lines, 4);
+}
@@ -0,0 +1,109 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{
+    parse_file_diffs, process_file_diffs, ConfigError, FilterErrorPolicy, PatchSet, ProcessConfig,
+};
+
+fn reduce(input: &str) -> String {
+    let config = ProcessConfig {
+        suppression_patterns: ProcessConfig::unused_variable_suppression_patterns(),
+        ..ProcessConfig::default()
+    };
+    PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+}
+
+#[test]
+fn cc_void_cast() {
+    let output = reduce(include_str!("fixtures/suppression_cc.diff"));
+    assert!(!output.contains("(void)flags;"), "{output}");
+    assert!(!output.contains("std::ignore"), "{output}");
+    // Added along with a real change, so the whole block stays.
+    assert!(
+        output.contains("+  (void)force;\n+  Log(\"stopping\");\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn rust_let_underscore() {
+    let output = reduce(include_str!("fixtures/suppression_rs.diff"));
+    assert!(!output.contains("let _ = flags;"), "{output}");
+    assert!(!output.contains("_ = result;"), "{output}");
+    assert!(
+        output.contains("+    let _ = force;\n+    log(\"stopping\");\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn suppressions_are_kept_by_default() {
+    let input = include_str!("fixtures/suppression_cc.diff");
+    let output = PatchSet::from(process_file_diffs(
+        parse_file_diffs(input),
+        &ProcessConfig::default(),
+    ))
+    .to_string();
+    assert!(output.contains("+  (void)flags;\n"), "{output}");
+}
+
+#[test]
+fn filter_error_policy() {
+    let too_large = r"\w{1000}{1000}";
+    let builder = || {
+        ProcessConfig::builder()
+            .suppression_pattern(r"^\s*\(void\)\s*\w+;$")
+            .suppression_pattern(too_large)
+    };
+    assert!(matches!(
+        builder().build(),
+        Err(ConfigError::InvalidPattern { pattern, .. }) if pattern == too_large
+    ));
+    let input = include_str!("fixtures/suppression_cc.diff");
+    let reduce = |policy| {
+        let config = builder().on_filter_error(policy).build().unwrap();
+        assert_eq!(config.suppression_patterns.len(), 1);
+        assert_eq!(config.rejected_suppression_patterns, [too_large]);
+        PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string()
+    };
+
+    // The pattern that compiled still elides what it matches, and the blocks the other one would
+    // have been checked against are kept.
+    let output = reduce(FilterErrorPolicy::Warn);
+    assert!(!output.contains("(void)flags;"), "{output}");
+    assert!(output.contains("+  std::ignore = result;\n"), "{output}");
+    assert!(
+        output.contains("+  (void)force;\n+  Log(\"stopping\");\n"),
+        "{output}"
+    );
+
+    // Or they are elided.
+    assert_eq!(reduce(FilterErrorPolicy::Ignore), "");
+
+    // A pattern that does not parse is handled the same way.
+    assert!(ProcessConfig::builder()
+        .attribute_pattern("(")
+        .build()
+        .is_err());
+    let config = ProcessConfig::builder()
+        .suppression_pattern("(")
+        .on_filter_error(FilterErrorPolicy::Ignore)
+        .build()
+        .unwrap();
+    assert_eq!(config.rejected_suppression_patterns, ["("]);
+    assert_eq!(
+        PatchSet::from(process_file_diffs(parse_file_diffs(input), &config)).to_string(),
+        ""
+    );
+}
@@ -0,0 +1,80 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::test_runner::{run_fixtures, FixtureOutcome};
+use diffreducer::ProcessConfig;
+use std::fs;
+use std::process::Command;
+
+const MECHANICAL: &str =
+    "--- a/a.cc\n+++ b/a.cc\n@@ -1,2 +1,2 @@\n x\n-  NOTREACHED_NORETURN();\n+  NOTREACHED();\n";
+const REAL: &str = "--- a/b.cc\n+++ b/b.cc\n@@ -1,2 +1,2 @@\n x\n-  int y = 1;\n+  int y = 2;\n";
+
+#[test]
+fn fixtures() {
+    let dir = std::env::temp_dir().join(format!("diffreducer-fixtures-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("mechanical.input.diff"), MECHANICAL).unwrap();
+    fs::write(dir.join("mechanical.expected.diff"), "").unwrap();
+    fs::write(dir.join("real.input.diff"), REAL).unwrap();
+    fs::write(dir.join("real.expected.diff"), format!("{REAL}\n")).unwrap();
+    fs::write(dir.join("notes.txt"), REAL).unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_diffreducer"))
+            .arg("--test-mode")
+            .arg(&dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = run(&[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "PASS mechanical\nPASS real\n"
+    );
+
+    fs::write(dir.join("real.expected.diff"), "").unwrap();
+    fs::write(dir.join("unexpected.input.diff"), REAL).unwrap();
+    let results = run_fixtures(&dir, &ProcessConfig::default()).unwrap();
+    let outcomes = results
+        .iter()
+        .map(|result| (result.name.as_str(), &result.outcome))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        [
+            ("mechanical", &FixtureOutcome::Pass),
+            (
+                "real",
+                &FixtureOutcome::Fail {
+                    expected: String::new(),
+                    actual: format!("{REAL}\n"),
+                }
+            ),
+            ("unexpected", &FixtureOutcome::MissingExpected),
+        ]
+    );
+    let output = run(&[]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("FAIL real: output differs from "),
+        "{stdout}"
+    );
+    assert!(stdout.contains("FAIL unexpected: "), "{stdout}");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::similarity::SimilarityMetric;
+use diffreducer::{ProcessConfig, Replacement, ValidationWarning};
+
+#[test]
+fn default_config_has_no_warnings() {
+    assert_eq!(ProcessConfig::default().validate(), []);
+}
+
+#[test]
+fn warnings() {
+    let config = ProcessConfig {
+        replacements: Vec::new(),
+        ..ProcessConfig::default()
+    };
+    assert_eq!(config.validate(), [ValidationWarning::NoReplacements]);
+
+    let config = ProcessConfig {
+        replacements: vec![
+            Replacement::new("Foo", "Foo"),
+            Replacement::new("Bar", "Baz"),
+        ],
+        ..ProcessConfig::default()
+    };
+    let warnings = config.validate();
+    assert_eq!(
+        warnings,
+        [ValidationWarning::NoOpReplacement {
+            before: "Foo".to_owned()
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "replacement \"Foo\" has identical before and after"
+    );
+}
+
+#[test]
+fn low_similarity_threshold() {
+    let config = |metric, threshold| ProcessConfig {
+        similarity_metric: metric,
+        similarity_threshold: threshold,
+        ..ProcessConfig::default()
+    };
+    assert_eq!(
+        config(SimilarityMetric::Jaccard, 0.3).validate(),
+        [ValidationWarning::LowSimilarityThreshold { threshold: 0.3 }]
+    );
+    // A high threshold only elides blocks that are nearly identical.
+    assert_eq!(config(SimilarityMetric::Jaccard, 0.9).validate(), []);
+    // The exact metric ignores the threshold.
+    assert_eq!(config(SimilarityMetric::Exact, 0.3).validate(), []);
+}
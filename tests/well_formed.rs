@@ -0,0 +1,68 @@
+// Copyright 2024 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use diffreducer::{parse_file_diffs, ValidationErrorKind};
+
+#[test]
+fn well_formed_fixture() {
+    let file_diffs = parse_file_diffs(include_str!("fixtures/multiple_hunks.diff"));
+    assert_eq!(file_diffs[0].well_formed(), Ok(()));
+}
+
+#[test]
+fn out_of_order_and_overlapping_chunks() {
+    let input = concat!(
+        "--- a/foo.cc\n+++ b/foo.cc\n",
+        "@@ -10,2 +10,2 @@\n a\n-b\n+c\n",
+        "@@ -11,2 +11,2 @@\n d\n-e\n+f\n",
+        "@@ -1,2 +1,2 @@\n g\n-h\n+i\n",
+    );
+    let errors = parse_file_diffs(input)[0].well_formed().unwrap_err();
+    let kinds = errors
+        .iter()
+        .map(|error| (error.line, error.kind.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (11, ValidationErrorKind::Overlapping),
+            (1, ValidationErrorKind::OutOfOrder)
+        ]
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "foo.cc:11: chunk overlaps the previous one"
+    );
+}
+
+#[test]
+fn count_mismatch() {
+    // The parser keeps the extra removed line, so the body disagrees with the header.
+    let input = "--- a/foo.cc\n+++ b/foo.cc\n@@ -1,2 +1,3 @@\n a\n-b\n-x\n+c\n";
+    let errors = parse_file_diffs(input)[0].well_formed().unwrap_err();
+    let kinds = errors.iter().map(|error| &error.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            &ValidationErrorKind::OldCountMismatch {
+                header: 2,
+                actual: 3
+            },
+            &ValidationErrorKind::NewCountMismatch {
+                header: 3,
+                actual: 2
+            }
+        ]
+    );
+}